@@ -0,0 +1,232 @@
+//! A Language Server Protocol frontend wrapping `Engine`.
+//!
+//! The bespoke JSON (`slicer`) and gRPC (`sce::rpc::Sce`) protocols both require a caller to know
+//! about this project specifically; this binary instead speaks LSP directly, so any LSP-capable
+//! editor can drive slice/inline/extract as ordinary Code Actions, no bespoke client needed. This
+//! is particularly useful for C/C++, where `InlineRequest`'s doc comment already notes most LSPs
+//! (clangd included) don't offer an inline refactor of their own.
+use std::collections::HashMap;
+use std::path::Path;
+
+use tokio::sync::Mutex;
+use tower_lsp::jsonrpc::Result as RpcResult;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+use sce::engine::{Engine, Symbol};
+use sce::engine_config::from_guessed_language;
+use sce::guess_language::guess as guess_language;
+use sce::rpc::SliceDirection;
+
+/// Translate an LSP `Position` (UTF-16 code units within a line) into a `tree_sitter::Point`
+/// (bytes within a line) - tree-sitter works in bytes, LSP positions don't.
+fn to_ts_point(src: &str, position: Position) -> tree_sitter::Point {
+    let line = src.split('\n').nth(position.line as usize).unwrap_or("");
+
+    let mut utf16_count: u32 = 0;
+    let mut byte_col = line.len();
+    for (byte_idx, c) in line.char_indices() {
+        if utf16_count >= position.character {
+            byte_col = byte_idx;
+            break;
+        }
+        utf16_count += c.len_utf16() as u32;
+    }
+
+    tree_sitter::Point{row: position.line as usize, column: byte_col}
+}
+
+/// The inverse of `to_ts_point`: a byte column within `src`'s line back into UTF-16 units.
+fn to_lsp_position(src: &str, point: tree_sitter::Point) -> Position {
+    let line = src.split('\n').nth(point.row).unwrap_or("");
+    let utf16_col = line[..point.column.min(line.len())].encode_utf16().count();
+    Position{line: point.row as u32, character: utf16_col as u32}
+}
+
+fn to_lsp_range(src: &str, range: tree_sitter::Range) -> Range {
+    Range{start: to_lsp_position(src, range.start_point), end: to_lsp_position(src, range.end_point)}
+}
+
+/// `Range` over LSP only carries `Position`s (line/UTF-16 column), so reconstructing a
+/// `tree_sitter::Range` (which also wants byte offsets) means walking `src` to find each point's
+/// byte offset.
+fn byte_at_point(src: &str, point: tree_sitter::Point) -> usize {
+    let mut row = 0;
+    let mut col = 0;
+    for (i, c) in src.char_indices() {
+        if row == point.row && col == point.column {
+            return i;
+        }
+        if c == '\n' {
+            row += 1;
+            col = 0;
+        } else {
+            col += c.len_utf8();
+        }
+    }
+    src.len()
+}
+
+fn to_ts_range(src: &str, range: Range) -> tree_sitter::Range {
+    let start_point = to_ts_point(src, range.start);
+    let end_point = to_ts_point(src, range.end);
+
+    tree_sitter::Range{
+        start_byte: byte_at_point(src, start_point),
+        end_byte: byte_at_point(src, end_point),
+        start_point,
+        end_point,
+    }
+}
+
+/// The range spanning the whole document, for edits (like inline) that replace the entire file.
+fn full_document_range(src: &str) -> Range {
+    let lines: Vec<&str> = src.split('\n').collect();
+    let last_row = lines.len().saturating_sub(1);
+    let last_col = lines.last().map(|l| l.encode_utf16().count()).unwrap_or(0);
+    Range{start: Position{line: 0, character: 0}, end: Position{line: last_row as u32, character: last_col as u32}}
+}
+
+/// Depth-first search of an outline for the first symbol named `name`, used to resolve a callee
+/// identifier at a call-site into the point of its definition (within the same document).
+fn find_symbol_point(symbols: &[Symbol], name: &str) -> Option<tree_sitter::Point> {
+    for symbol in symbols {
+        if symbol.name == name {
+            return Some(symbol.range.start_point);
+        }
+        if let Some(point) = find_symbol_point(&symbol.children, name) {
+            return Some(point);
+        }
+    }
+    None
+}
+
+fn workspace_edit(uri: Url, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEdit{changes: Some(HashMap::from([(uri, edits)])), document_changes: None, change_annotations: None}
+}
+
+fn code_action(title: &str, kind: CodeActionKind, edit: WorkspaceEdit) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction{
+        title: title.to_string(),
+        kind: Some(kind),
+        edit: Some(edit),
+        ..Default::default()
+    })
+}
+
+struct Backend {
+    client: Client,
+    documents: Mutex<HashMap<Url, String>>,
+}
+
+impl Backend {
+    fn make_engine(uri: &Url, src: &str) -> Option<Engine> {
+        let lang = guess_language(Path::new(uri.path()), src)?;
+        Some(Engine{config: from_guessed_language(lang)?, src: src.to_string()})
+    }
+
+    fn slice_action(engine: &mut Engine, uri: &Url, point: tree_sitter::Point, direction: SliceDirection, title: &str, kind: CodeActionKind) -> Option<CodeActionOrCommand> {
+        let to_remove = engine.slice(point, direction).ok()?;
+        let edits = to_remove.into_iter().map(|range| TextEdit{range: to_lsp_range(&engine.src, range), new_text: String::new()}).collect();
+        Some(code_action(title, kind, workspace_edit(uri.clone(), edits)))
+    }
+
+    /// Offer inlining the call at `point`, if it resolves to a definition elsewhere in the same
+    /// document. Cross-file inlining would need a project-wide symbol index, which this frontend
+    /// doesn't have - it only ever looks within the open document.
+    fn inline_action(engine: &mut Engine, uri: &Url, point: tree_sitter::Point) -> Option<CodeActionOrCommand> {
+        let callee = engine.identifier_at_point(point).ok()??;
+        let symbols = engine.outline().ok()?;
+        let target_point = find_symbol_point(&symbols, &callee)?;
+
+        let content = engine.inline(point, &engine.src.clone(), target_point).ok()?;
+        let edit = TextEdit{range: full_document_range(&engine.src), new_text: content};
+        Some(code_action("Inline function call", CodeActionKind::REFACTOR_INLINE, workspace_edit(uri.clone(), vec![edit])))
+    }
+
+    /// Offer extracting the statements spanning the selection into a new function. Like
+    /// `inline_action`, this replaces the whole document rather than just the selection, since the
+    /// new function definition and the rewritten call site land in two different places.
+    fn extract_action(engine: &mut Engine, uri: &Url, range: tree_sitter::Range) -> Option<CodeActionOrCommand> {
+        let content = engine.extract(range).ok()?;
+        let edit = TextEdit{range: full_document_range(&engine.src), new_text: content};
+        Some(code_action("Extract function", CodeActionKind::REFACTOR_EXTRACT, workspace_edit(uri.clone(), vec![edit])))
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> RpcResult<InitializeResult> {
+        Ok(InitializeResult{
+            capabilities: ServerCapabilities{
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions{
+                    code_action_kinds: Some(vec![CodeActionKind::REFACTOR_EXTRACT, CodeActionKind::REFACTOR_INLINE]),
+                    work_done_progress_options: Default::default(),
+                    resolve_provider: Some(false),
+                })),
+                ..Default::default()
+            },
+            server_info: None,
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        self.client.log_message(MessageType::INFO, "sce-lsp initialized").await;
+    }
+
+    async fn shutdown(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.documents.lock().await.insert(params.text_document.uri, params.text_document.text);
+    }
+
+    async fn did_change(&self, mut params: DidChangeTextDocumentParams) {
+        // We advertised `TextDocumentSyncKind::FULL`, so each change carries the whole new text.
+        if let Some(change) = params.content_changes.pop() {
+            self.documents.lock().await.insert(params.text_document.uri, change.text);
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        self.documents.lock().await.remove(&params.text_document.uri);
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> RpcResult<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+
+        let src = match self.documents.lock().await.get(&uri) {
+            Some(src) => src.clone(),
+            None => return Ok(None),
+        };
+
+        let point = to_ts_point(&src, params.range.start);
+        let mut engine = match Self::make_engine(&uri, &src) {
+            Some(engine) => engine,
+            None => return Ok(None),
+        };
+
+        let range = to_ts_range(&src, params.range);
+
+        let mut actions = vec![];
+        actions.extend(Self::slice_action(&mut engine, &uri, point, SliceDirection::Backward, "Slice backward from cursor", CodeActionKind::REFACTOR_EXTRACT));
+        actions.extend(Self::slice_action(&mut engine, &uri, point, SliceDirection::Forward, "Slice forward from cursor", CodeActionKind::REFACTOR_EXTRACT));
+        actions.extend(Self::inline_action(&mut engine, &uri, point));
+        actions.extend(Self::extract_action(&mut engine, &uri, range));
+
+        Ok(Some(actions))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+
+    let (service, socket) = LspService::new(|client| Backend{client, documents: Mutex::new(HashMap::new())});
+    Server::new(stdin, stdout, socket).serve(service).await;
+}
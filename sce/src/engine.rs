@@ -0,0 +1,886 @@
+//! `slicer::slicer` forked off of this module, and `sce` has been frozen since - but neither crate
+//! depends on the other, so a correctness fix to shared logic (dataflow, traversal, the
+//! `extract`/`inline` operations themselves) only lands in whichever copy someone happened to
+//! touch. `render.rs` and `guess_language.rs` are near-verbatim copies of their `slicer/`
+//! counterparts for the same reason. Until `sce` depends on `slicer` instead of carrying its own
+//! copy, check whether a fix made here also applies to `slicer/src/slicer.rs` (or vice versa).
+
+use std::cell::RefCell;
+use std::collections::{HashSet, HashMap};
+use std::hash::{Hash, Hasher};
+use thiserror::Error;
+
+use crate::traverse::depth_first;
+use crate::engine_config::EngineConfig;
+use crate::rpc::SliceDirection;
+
+/// Represents a symbol name, represented as the list of components which make up the symbol
+/// e.g. ["self", "foo", "bar"] in the case of `self.foo.bar` in Python.
+/// This lets us easily check if a variable affects/is affected by another (in name).
+#[derive(Clone, Debug)]
+struct NameRef<'a> {
+    node: tree_sitter::Node<'a>,
+    components: Vec<String>,
+}
+
+impl<'a> PartialEq for NameRef<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.components == other.components
+    }
+}
+
+impl<'a> Eq for NameRef<'a> {}
+
+impl<'a> Hash for NameRef<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // we don't care about the node itself, just the name
+        self.components.hash(state);
+    }
+}
+
+impl<'a> NameRef<'a> {
+    fn affects(&self, other: &NameRef) -> bool {
+        let len = self.components.len().min(other.components.len());
+        return self.components[..len].iter().zip(other.components[..len].iter()).all(|(a, b)| a == b);
+    }
+}
+
+struct InlineTempVar {
+    name: String,
+    value: String,
+    typ: String,
+}
+
+impl InlineTempVar {
+    fn format(&self, fmt: &str) -> String {
+        fmt.clone()
+        .replace("{name}", &self.name)
+        .replace("{value}", &self.value)
+        .replace("{type}", &self.typ)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SliceError {
+    #[error("tree-sitter version mismatch: {0}")]
+    TreeSitterVersionError(tree_sitter::LanguageError),
+    #[error("No identifier at point {0}")]
+    NoNameAtPointError(tree_sitter::Point),
+    #[error("No call at point {0}")]
+    NoCallAtPointError(tree_sitter::Point),
+    #[error("extract: span writes to multiple variables still used afterwards ({0:?}), but only a single return value is supported")]
+    MultipleExtractOutputsError(Vec<String>),
+}
+
+pub struct Engine {
+    pub config: EngineConfig,
+    pub src: String,
+}
+
+/// A single entry in a file's outline: a named definition (function, method, class, ...) along
+/// with whatever other definitions are nested within it.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: &'static str,
+    pub range: tree_sitter::Range,
+    pub children: Vec<Symbol>,
+}
+
+#[derive(Debug)]
+enum RewriteValue<'a> {
+    None,
+    String(String),
+    Node(tree_sitter::Node<'a>),
+}
+
+impl Engine {
+    fn contains_subtype(&self, types: &Vec<&'static str>, node: &tree_sitter::Node) -> bool {
+        types.iter().any(|t| self.config.subtypes[&t.to_string()].contains(&node.kind().to_string()))
+    }
+
+    /// Return a Vec of all "name components", e.g. ["self", "foo", "bar"]
+    fn name_components(&self, node: &tree_sitter::Node) -> Vec<String> {
+        depth_first(*node)
+            .filter(|&descendant| self.config.identifier_types.contains(&descendant.kind()))
+            .map(|descendant| String::from(&self.src[descendant.byte_range()]))
+            .into_iter().collect()
+    }
+
+    fn node_of_kind_for_point<'a>(&self, root: &'a tree_sitter::Node, kinds: &Vec<&'static str>, point: tree_sitter::Point) -> Option<tree_sitter::Node<'a>> {
+        let mut cur = root.walk();
+
+        loop {
+            let node = cur.node();
+
+            if kinds.contains(&node.kind()) {
+                return Some(node);
+            }
+
+            // Either we progress down to a child node which contains the point, or we bail out.
+            if cur.goto_first_child_for_point(point) == None {
+                return None;
+            }
+        }
+    }
+
+    /// Find the name reference at the specified point, if an identifier is referenced at that
+    /// point.
+    fn name_at_point<'a>(&self, root: &'a tree_sitter::Node, point: tree_sitter::Point) -> Option<NameRef<'a>> {
+        let node = self.node_of_kind_for_point(root, &self.config.name_types, point)?;
+        Some(NameRef{node, components: self.name_components(&node)})
+    }
+
+    /// List all names referenced by this node or any descendant.
+    fn referenced_names<'a>(&self, node: tree_sitter::Node<'a>) -> Vec<NameRef<'a>> {
+        let mut names = vec![];
+        depth_first(node).traverse(|descendant| {
+            if self.config.name_types.contains(&descendant.kind()) {
+                names.push(NameRef{node: descendant.clone(), components: self.name_components(&descendant)});
+                return false;
+            }
+            return true;
+        });
+        names
+    }
+
+    /// Propagate the set of target names out through all assignments until we hit a fixed point.
+    fn propagate_targets<'a>(&self, outer_scope: &'a tree_sitter::Node, initial_target_names: &HashSet<NameRef<'a>>, direction: SliceDirection) -> HashSet<NameRef<'a>> {
+        let mut target_names = initial_target_names.clone();
+
+        loop {
+            let len_before = target_names.len();
+
+            for descendant in depth_first(*outer_scope) {
+                if let Some((_, (defs_child_name, refs_child_name))) = self.config.propagating_types.iter().find(|&&(expr_kind, (_, _))| expr_kind == descendant.kind()) {
+                    let defs_node = descendant.child_by_field_name(defs_child_name);
+                    let refs_node = descendant.child_by_field_name(refs_child_name);
+
+                    // Guard against things like python's `with` which may or may not define
+                    // variable(s)
+                    if defs_node.is_none() || refs_node.is_none() {
+                        continue;
+                    }
+
+                    let node_defs_names = self.referenced_names(defs_node.unwrap());
+                    let node_refs_names = self.referenced_names(refs_node.unwrap());
+                    log::debug!("defs {:?} refs {:?}", node_defs_names, node_refs_names);
+
+                    match direction {
+                        SliceDirection::Backward => {
+                            // if any known target is used in a defs, all refss in the
+                            // assign should now be targets
+                            if target_names.iter().any(|tname| node_defs_names.iter().any(|dname| tname.affects(&dname))) {
+                                log::info!("Propagating node {:?} adds {:?} to targets", descendant, node_refs_names);
+                                target_names.extend(node_refs_names.clone());
+                            }
+                        },
+                        SliceDirection::Forward => {
+                            // opposite: if any known target is used in a refs, all defss
+                            // should be targets.
+                            if target_names.iter().any(|tname| node_refs_names.iter().any(|sname| tname.affects(&sname))) {
+                                log::info!("Propagating node {:?} adds {:?} to targets", descendant, node_defs_names);
+                                target_names.extend(node_defs_names.clone());
+                            }
+                        },
+                    }
+                }
+            }
+
+            if target_names.len() == len_before {
+                break;
+            }
+        }
+
+        target_names
+    }
+
+    /// Returns an in-order Vec of the highest-level statement-type nodes which do not reference
+    /// any target name.
+    fn flatten_unreferenced<'a>(&self, target_func: tree_sitter::Node<'a>, target_names: &HashSet<NameRef<'a>>) -> Vec<tree_sitter::Node<'a>> {
+        let mut delete_nodes = vec![];
+
+        let references = RefCell::new(HashSet::new());
+
+        depth_first(target_func).traverse_with_depth(
+            |descendant| {
+                if self.config.name_types.contains(&descendant.kind()) {
+                    let name = NameRef{node: descendant, components: self.name_components(&descendant)};
+                    if target_names.iter().any(|tname| tname.affects(&name)) {
+                        references.borrow_mut().insert(descendant);
+                    }
+                    return false;
+                }
+                return true;
+            },
+            |_, _|{},
+            |_, to| {
+                let mut cur = to.walk();
+                for child in to.children(&mut cur) {
+                    if references.borrow().get(&child).is_some() {
+                        references.borrow_mut().insert(to);
+                        break;
+                    }
+                }
+            }
+        );
+
+        depth_first(target_func).traverse(|statement| {
+            if !self.contains_subtype(&self.config.statement_types, &statement) {
+                return true;
+            }
+
+            if !references.borrow().contains(&statement) {
+                let mut parent = statement;
+                let parent_deleted = loop {
+                    match parent.parent() {
+                        Some(n) => {
+                            parent = n;
+                        }
+                        None => {
+                            break false;
+                        }
+                    }
+
+                    if delete_nodes.contains(&parent) {
+                        break true;
+                    }
+                };
+
+                if !parent_deleted {
+                    delete_nodes.push(statement);
+                }
+            }
+
+            return true;
+        });
+
+        delete_nodes
+    }
+
+    /// Coalesce adjacent deleted spans only if they are adjacent in the AST
+    fn coalesce_ranges<'a>(&self, nodes: &Vec<tree_sitter::Node<'a>>) -> Vec<tree_sitter::Range> {
+        let mut ranges = vec![];
+
+        let mut i = 0;
+        while i < nodes.len() {
+            let start = (nodes[i].start_byte(), nodes[i].start_position());
+
+            let mut end_node = nodes[i];
+
+            while i + 1 < nodes.len() {
+                let mut next = end_node.next_sibling();
+
+                let mut cur = end_node;
+                while next.is_none() {
+                    match cur.parent() {
+                        Some(parent) => {
+                            cur = parent;
+                            next = parent.next_sibling();
+                        }
+                        None => {
+                            break;
+                        }
+                    }
+                }
+
+                match next {
+                    Some(next) => {
+                        if next == nodes[i + 1] {
+                            end_node = next;
+                            i += 1;
+                        } else {
+                            break;
+                        }
+                    },
+                    None => {
+                        break;
+                    }
+                }
+            }
+
+            let end = (end_node.end_byte(), end_node.end_position());
+
+            ranges.push(tree_sitter::Range{
+                start_byte: start.0,
+                start_point: start.1,
+                end_byte: end.0,
+                end_point: end.1,
+            });
+
+            i += 1;
+        }
+
+        ranges
+    }
+
+    pub fn slice(&mut self, target_point: tree_sitter::Point, direction: SliceDirection) -> Result<Vec<tree_sitter::Range>, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+        self.slice_on_tree(tree.root_node(), target_point, direction)
+    }
+
+    /// The body of `slice`, taking an already-parsed root node so that `EngineSession` can reuse
+    /// a cached, incrementally-reparsed tree instead of parsing `src` from scratch.
+    fn slice_on_tree(&self, root_node: tree_sitter::Node, target_point: tree_sitter::Point, direction: SliceDirection) -> Result<Vec<tree_sitter::Range>, SliceError> {
+        log::debug!("sexp: {}", root_node.to_sexp());
+
+        let target_name = self.name_at_point(&root_node, target_point).ok_or(SliceError::NoNameAtPointError(target_point))?;
+        log::debug!("targeting {:?}", target_name);
+
+        let mut target_func = target_name.node;
+
+        loop {
+            if self.config.slice_scope_types.contains(&target_func.kind()) {
+                break;
+            }
+            target_func = target_func.parent().unwrap();
+        };
+
+        let mut target_names: HashSet<NameRef> = HashSet::new();
+        target_names.insert(target_name.clone());
+
+        target_names = self.propagate_targets(&target_func, &target_names, direction);
+        log::info!("Final set of target names: {:?}", target_names);
+        let delete_nodes = self.flatten_unreferenced(target_func, &target_names);
+        let delete_ranges = self.coalesce_ranges(&delete_nodes);
+
+        Ok(delete_ranges)
+    }
+
+    fn get_capture<'a>(&self, query: &tree_sitter::Query, capture_name: &str, node: tree_sitter::Node<'a>, content: &[u8]) -> Vec<tree_sitter::Node<'a>> {
+        let capture_idx = query.capture_index_for_name(capture_name).unwrap();
+        let mut cursor = tree_sitter::QueryCursor::new();
+
+        cursor
+        .captures(query, node, content)
+        .map(|(m, _)| m.captures.iter().filter(|c| c.index == capture_idx).map(|c| c.node)).into_iter().flatten().collect()
+    }
+
+    fn get_captures<'a, const COUNT: usize>(&self, query: &tree_sitter::Query, capture_names: [&str; COUNT], node: tree_sitter::Node<'a>, content: &[u8]) -> Vec<[tree_sitter::Node<'a>; COUNT]> {
+        let capture_idxs: Vec<u32> = capture_names.iter().map(|name| query.capture_index_for_name(name).unwrap()).collect();
+        let mut cursor = tree_sitter::QueryCursor::new();
+
+        cursor
+        .matches(query, node, content)
+        .map(|m| {
+            let capture_map: HashMap<u32, tree_sitter::Node> = m.captures.iter().map(|c| (c.index, c.node)).collect();
+            capture_idxs.iter().map(|idx| capture_map[idx]).collect::<Vec<tree_sitter::Node>>().try_into().unwrap()
+        }).collect()
+    }
+
+    fn rewrite_names(&self, node: &tree_sitter::Node, rename_map: &HashMap<NameRef, String>, src: &str) -> String {
+        let mut rewritten_src: String = String::new();
+
+        let mut prev_byte = node.start_byte();
+        depth_first(*node).traverse(|n| {
+            if self.config.name_types.contains(&n.kind()) {
+                let name = NameRef{node: n, components: self.name_components(&n)};
+                if let Some(new_name) = rename_map.get(&name) {
+                    rewritten_src += &src[prev_byte..n.start_byte()];
+                    rewritten_src += new_name;
+                    prev_byte = n.end_byte();
+                }
+
+                return false;
+            }
+
+            return true;
+        });
+        rewritten_src += &src[prev_byte..node.end_byte()];
+
+        rewritten_src
+    }
+
+    /// Find the identifier token at `point`, if any - e.g. to resolve the callee name at a
+    /// call-site for call-site -> definition lookups (used by the LSP frontend's inline code
+    /// action).
+    pub fn identifier_at_point(&mut self, point: tree_sitter::Point) -> Result<Option<String>, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+        let root_node = tree.root_node();
+
+        Ok(self.node_of_kind_for_point(&root_node, &self.config.identifier_types, point)
+            .map(|n| self.src[n.byte_range()].to_string()))
+    }
+
+    /// Find the name of a definition node for outline purposes: the first identifier-type
+    /// descendant of its `declarator`/`name` field if it has one (since searching the whole node
+    /// would also walk into the body and pick up unrelated identifiers), falling back to searching
+    /// the whole node for languages without such a field.
+    fn outline_name(&self, node: &tree_sitter::Node) -> String {
+        let search_root = node.child_by_field_name("declarator")
+            .or_else(|| node.child_by_field_name("name"))
+            .unwrap_or(*node);
+
+        depth_first(search_root)
+            .find(|descendant| self.config.identifier_types.contains(&descendant.kind()))
+            .map(|n| self.src[n.byte_range()].to_string())
+            .unwrap_or_else(|| "<anonymous>".to_string())
+    }
+
+    /// Build a nested tree of the definitions in the file (per `config.outline_types`), e.g. for
+    /// use as a file outline/symbol list.
+    ///
+    /// This is built on top of `traverse_with_depth`: `on_descent`/`on_ascent` track the current
+    /// tree depth, and a `Vec` of "children found so far" is kept per depth. A definition found at
+    /// depth `d` collects everything found between the DESCEND into its first child and the
+    /// ASCEND back out of it - i.e. everything at depth `d`'s children level - as its `children`.
+    pub fn outline(&mut self) -> Result<Vec<Symbol>, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+
+        let mut levels: Vec<Vec<Symbol>> = vec![vec![]];
+        let mut open: HashMap<usize, (String, &'static str, tree_sitter::Range)> = HashMap::new();
+        let mut depth = 0usize;
+
+        depth_first(tree.root_node()).traverse_with_depth(
+            |node| {
+                if let Some(&(_, kind)) = self.config.outline_types.iter().find(|&&(node_kind, _)| node_kind == node.kind()) {
+                    open.insert(depth, (self.outline_name(&node), kind, node.range()));
+                }
+                true
+            },
+            |_, _| {
+                depth += 1;
+                levels.push(vec![]);
+            },
+            |_, _| {
+                let children = levels.pop().unwrap();
+                depth -= 1;
+                match open.remove(&depth) {
+                    Some((name, kind, range)) => levels[depth].push(Symbol{name, kind, range, children}),
+                    None => levels[depth].extend(children),
+                }
+            },
+        );
+
+        Ok(levels.pop().unwrap())
+    }
+
+    /// Extract the statements spanning `range` into a newly synthesized function, replacing them
+    /// at the call site with a call to it. This is the inverse of `inline`.
+    ///
+    /// Parameters are inferred as the free variables read within the span whose definition lives
+    /// before it (a backward scan for a `propagating_types` assignment/declaration to that name
+    /// preceding the span); the out-param/return value is a variable written within the span that
+    /// is still referenced afterwards. Only a single out-param is supported - if the span writes to
+    /// more than one variable that's still live afterwards, `MultipleExtractOutputsError` is
+    /// returned rather than silently dropping all but one of them, since real multi-value returns
+    /// need out-params or a struct return, which C (the only language wired up today) doesn't have
+    /// a single idiomatic answer for.
+    pub fn extract(&mut self, range: tree_sitter::Range) -> Result<String, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+        let root_node = tree.root_node();
+
+        let enclosing = self.node_of_kind_for_point(&root_node, &self.config.slice_scope_types, range.start_point)
+            .ok_or(SliceError::NoNameAtPointError(range.start_point))?;
+
+        // Gather the top-level statements within `enclosing` that the span covers.
+        let mut span_nodes = vec![];
+        depth_first(enclosing).traverse(|n| {
+            if n.start_byte() >= range.start_byte && n.end_byte() <= range.end_byte && self.contains_subtype(&self.config.statement_types, &n) {
+                span_nodes.push(n);
+                return false;
+            }
+            n.start_byte() < range.end_byte && n.end_byte() > range.start_byte
+        });
+
+        if span_nodes.is_empty() {
+            return Err(SliceError::NoNameAtPointError(range.start_point));
+        }
+
+        let span_start = span_nodes.iter().map(|n| n.start_byte()).min().unwrap();
+        let span_end = span_nodes.iter().map(|n| n.end_byte()).max().unwrap();
+
+        // Free identifiers read anywhere in the span.
+        let mut read_names: Vec<String> = vec![];
+        for span_node in &span_nodes {
+            depth_first(*span_node).traverse(|d| {
+                if self.config.identifier_types.contains(&d.kind()) {
+                    let name = self.src[d.byte_range()].to_string();
+                    if !read_names.contains(&name) {
+                        read_names.push(name);
+                    }
+                    return false;
+                }
+                true
+            });
+        }
+
+        // Names assigned to within the span, via `propagating_types`.
+        let mut written_names: Vec<String> = vec![];
+        for span_node in &span_nodes {
+            depth_first(*span_node).traverse(|d| {
+                if let Some((_, (defs_field, _))) = self.config.propagating_types.iter().find(|&&(kind, _)| kind == d.kind()) {
+                    if let Some(defs_node) = d.child_by_field_name(defs_field) {
+                        let name = self.src[defs_node.byte_range()].to_string();
+                        if !written_names.contains(&name) {
+                            written_names.push(name);
+                        }
+                    }
+                }
+                true
+            });
+        }
+
+        let is_defined_before = |name: &str| {
+            let mut found = false;
+            depth_first(enclosing).traverse(|n| {
+                if n.start_byte() >= span_start {
+                    return false;
+                }
+                if let Some((_, (defs_field, _))) = self.config.propagating_types.iter().find(|&&(kind, _)| kind == n.kind()) {
+                    if n.child_by_field_name(defs_field).map_or(false, |d| &self.src[d.byte_range()] == name) {
+                        found = true;
+                    }
+                }
+                true
+            });
+            found
+        };
+
+        let is_used_after = |name: &str| {
+            let mut found = false;
+            depth_first(enclosing).traverse(|n| {
+                if n.start_byte() >= span_end && self.config.identifier_types.contains(&n.kind()) && &self.src[n.byte_range()] == name {
+                    found = true;
+                }
+                true
+            });
+            found
+        };
+
+        let params: Vec<&String> = read_names.iter().filter(|name| !written_names.contains(name) && is_defined_before(name)).collect();
+        let out_vars: Vec<&String> = written_names.iter().filter(|name| is_used_after(name)).collect();
+        if out_vars.len() > 1 {
+            return Err(SliceError::MultipleExtractOutputsError(out_vars.into_iter().cloned().collect()));
+        }
+        let out_var = out_vars.into_iter().next();
+
+        let function_name = "extracted_function";
+        let call_args = params.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
+
+        // TODO: reindent the body to the new function's nesting depth rather than reusing the
+        // original statements' indentation verbatim.
+        let body: String = span_nodes.iter().map(|n| &self.src[n.byte_range()]).collect::<Vec<_>>().join("\n    ");
+
+        let new_function = match out_var {
+            Some(out_var) => format!(
+                "int {}({}) {{\n    {}\n    return {};\n}}\n\n",
+                function_name, call_args, body, out_var,
+            ),
+            None => format!(
+                "void {}({}) {{\n    {}\n}}\n\n",
+                function_name, call_args, body,
+            ),
+        };
+
+        let call_site = match out_var {
+            Some(out_var) => format!("{} = {}({});", out_var, function_name, call_args),
+            None => format!("{}({});", function_name, call_args),
+        };
+
+        let mut new_src = self.src[..enclosing.start_byte()].to_string();
+        new_src += &new_function;
+        new_src += &self.src[enclosing.start_byte()..span_start];
+        new_src += &call_site;
+        new_src += &self.src[span_end..];
+
+        Ok(new_src)
+    }
+
+    pub fn inline(&mut self, point: tree_sitter::Point, target_content: &str, target_point: tree_sitter::Point) -> Result<String, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+        self.inline_on_tree(&mut parser, tree.root_node(), point, target_content, target_point)
+    }
+
+    /// `inline` against an already-parsed tree of `self.src`, so an `EngineSession` can reuse its
+    /// cached tree; only `target_content` is parsed here.
+    fn inline_on_tree(&mut self, parser: &mut tree_sitter::Parser, root_node: tree_sitter::Node, point: tree_sitter::Point, target_content: &str, target_point: tree_sitter::Point) -> Result<String, SliceError> {
+        let function_definition_file_tree = parser.parse(target_content, None).unwrap();
+        let function_definition_file_root_node = function_definition_file_tree.root_node();
+
+        let callsite = self.node_of_kind_for_point(&root_node, &self.config.function_call_types, point).ok_or(SliceError::NoCallAtPointError(point))?;
+        log::debug!("callsite: {}", callsite.to_sexp());
+        let function_definition = self.node_of_kind_for_point(&function_definition_file_root_node, &self.config.slice_scope_types, target_point).ok_or(SliceError::NoNameAtPointError(target_point))?;
+        log::debug!("function_definition: {}", function_definition.to_sexp());
+
+        let call_args = self.get_capture(&self.config.call_args_query, "value", callsite, self.src.as_bytes());
+        log::debug!("call_args: {:?}", call_args);
+
+        let function_params = self.get_captures(&self.config.function_query, ["param_name", "param_type"], function_definition, target_content.as_bytes());
+        log::debug!("function_params: {:?}", function_params);
+
+        let function = self.get_captures(&self.config.function_query, ["function_type", "function_body"], function_definition, target_content.as_bytes());
+        let [function_type, function_body] = function[0];
+        let returns = self.get_captures(&self.config.returns_query, ["return_statement", "return_value"], function_definition, target_content.as_bytes());
+
+        let mut temps: Vec<InlineTempVar> = vec![];
+
+        let mut rename_map: HashMap<NameRef, String> = HashMap::new();
+
+        for (arg, [param_name_node, param_type_node]) in call_args.iter().zip(function_params.iter()) {
+            let param_name = self.name_at_point(&function_definition_file_root_node, param_name_node.start_position()).ok_or(SliceError::NoNameAtPointError(param_name_node.start_position()))?;
+
+            if self.config.constant_types.contains(&arg.kind()) || self.config.name_types.contains(&arg.kind()) {
+                rename_map.insert(param_name, self.src[arg.byte_range()].to_string());
+            } else {
+                let inline_name = format!("inline_{}", &target_content[param_name.node.byte_range()]);
+                temps.push(InlineTempVar{
+                    name: inline_name.clone(),
+                    value: self.src[arg.byte_range()].to_string(),
+                    typ: target_content[param_type_node.byte_range()].to_string(),
+                });
+                rename_map.insert(param_name, inline_name);
+            }
+        }
+        log::debug!("rename_map: {:?}", rename_map);
+
+        let mut rewrite_map: HashMap<tree_sitter::Node, RewriteValue> = HashMap::new();
+
+        let callsite_rewrite = match &returns[..] {
+            [ret] => {
+                let [return_stmt, retval] = ret;
+                rewrite_map.insert(return_stmt.clone(), RewriteValue::None);
+                RewriteValue::Node(retval.clone())
+            },
+            _ => {
+                RewriteValue::None
+            }
+        };
+
+        let src_lines: Vec<&str> = self.src.split("\n").collect();
+        let callsite_whitespace: String = src_lines[callsite.start_position().row].chars().take_while(|c| c.is_whitespace()).collect();
+
+        let mut new_src = src_lines[0..callsite.start_position().row].join("\n") + "\n";
+
+        for temp in temps {
+            new_src += &callsite_whitespace;
+            new_src += &temp.format(self.config.temp_var_format);
+            new_src += "\n";
+        }
+
+        let mut start_byte = 0;
+        let mut end_byte = 0;
+
+        let mut cur = function_body.walk();
+        for child in function_body.children(&mut cur) {
+            if child.is_named() {
+                if start_byte == 0 {
+                    start_byte = child.start_byte();
+                }
+                end_byte = child.end_byte();
+            }
+        }
+
+        let definition_whitespace: String = target_content[..end_byte].chars().rev().take_while(|c| c.is_whitespace()).collect();
+
+        let mut prev_byte = start_byte;
+
+        let mut inline_src: String = String::new();
+        depth_first(function_body.clone()).traverse(|n| {
+            if let Some(rewrite) = rewrite_map.get(&n) {
+                inline_src += &target_content[prev_byte..n.start_byte()];
+                match rewrite {
+                    RewriteValue::String(s) => inline_src += s,
+                    RewriteValue::Node(n) => inline_src += &self.rewrite_names(n, &rename_map, &target_content),
+                    RewriteValue::None => (),
+                }
+                prev_byte = n.end_byte();
+
+                return false;
+            } else if self.config.name_types.contains(&n.kind()) {
+                let name = NameRef{node: n, components: self.name_components(&n)};
+                if let Some(new_name) = rename_map.get(&name) {
+                    inline_src += &target_content[prev_byte..n.start_byte()];
+                    inline_src += new_name;
+                    prev_byte = n.end_byte();
+                }
+
+                return false;
+            }
+
+            return true;
+        });
+        inline_src += &target_content[prev_byte..end_byte];
+
+        for line in inline_src.split("\n") {
+            new_src += &callsite_whitespace;
+            new_src += &(line.strip_prefix(&definition_whitespace).unwrap_or(&line));
+            new_src += "\n";
+        }
+
+        new_src = new_src[..new_src.len()-1].to_string();
+
+        new_src += &src_lines[callsite.start_position().row][0..callsite.start_position().column];
+        match callsite_rewrite {
+            RewriteValue::String(s) => new_src += &s,
+            RewriteValue::Node(n) => new_src += &self.rewrite_names(&n, &rename_map, &target_content),
+            RewriteValue::None => (),
+        }
+
+        new_src += &self.src[callsite.end_byte()..];
+
+        Ok(new_src)
+    }
+}
+
+/// Delete the given ranges from the src, returning both the source with lines removed as well as
+/// the target_point adjusted to be pointing to the same location.
+/// Ranges is assumed to be pre-sorted.
+pub fn delete_ranges(src: &str, ranges: &Vec<tree_sitter::Range>, target_point: tree_sitter::Point) -> (String, tree_sitter::Point) {
+    let src_lines: Vec<&str> = src.split("\n").collect();
+
+    let mut target_point = target_point.clone();
+    let mut new: Vec<&str> = vec![];
+
+    let mut i = 0;
+    for range in ranges {
+        if i < range.start_point.row {
+            new.extend(src_lines[i..range.start_point.row].iter());
+        }
+        let prefix = &src_lines[range.start_point.row][0..range.start_point.column];
+        if !prefix.trim().is_empty() {
+            new.push(prefix);
+        }
+        let suffix = &src_lines[range.end_point.row][range.end_point.column..];
+        if !suffix.trim().is_empty() {
+            new.push(suffix);
+        }
+        i = range.end_point.row + 1;
+
+        if range.end_point.row < target_point.row {
+            let mut deleted_lines = range.end_point.row - range.start_point.row;
+            if prefix.trim().is_empty() || suffix.trim().is_empty() {
+                deleted_lines += 1;
+            }
+            target_point.row -= deleted_lines;
+        }
+    }
+    new.extend(src_lines[i..].iter());
+
+    (new.join("\n"), target_point)
+}
+
+fn point_at_byte(src: &str, byte: usize) -> tree_sitter::Point {
+    let mut row = 0;
+    let mut col = 0;
+    for b in src.as_bytes()[..byte].iter() {
+        if *b == b'\n' {
+            row += 1;
+            col = 0;
+        } else {
+            col += 1;
+        }
+    }
+    tree_sitter::Point::new(row, col)
+}
+
+/// Compute the `InputEdit` describing how `old_src` was rewritten into `new_src`, by diffing out
+/// the common prefix/suffix of the two buffers. Used to keep an `EngineSession`'s cached tree
+/// consistent with edits (like `inline`/`delete_ranges`) that are expressed as whole new buffers
+/// rather than as an edit directly.
+pub fn edit_for_replacement(old_src: &str, new_src: &str) -> tree_sitter::InputEdit {
+    let common_prefix = old_src.bytes().zip(new_src.bytes()).take_while(|(a, b)| a == b).count();
+
+    let old_rest = &old_src.as_bytes()[common_prefix..];
+    let new_rest = &new_src.as_bytes()[common_prefix..];
+    let common_suffix = old_rest.iter().rev().zip(new_rest.iter().rev()).take_while(|(a, b)| a == b).count()
+        .min(old_rest.len()).min(new_rest.len());
+
+    let start_byte = common_prefix;
+    let old_end_byte = old_src.len() - common_suffix;
+    let new_end_byte = new_src.len() - common_suffix;
+
+    tree_sitter::InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_at_byte(old_src, start_byte),
+        old_end_position: point_at_byte(old_src, old_end_byte),
+        new_end_position: point_at_byte(new_src, new_end_byte),
+    }
+}
+
+/// A long-lived session over a single buffer, holding a cached, incrementally-reparsed
+/// `tree_sitter::Tree` so that repeated `slice`/`inline` calls on the same file don't each pay
+/// for a full re-parse.
+///
+/// The key invariant this type maintains: any mutation made to `engine.src` (by `apply_edit`,
+/// `inline`, `delete_ranges`, or anything else added later) is always mirrored into an `InputEdit` applied to `tree` before
+/// the next parse, so the cached tree and `engine.src` never drift apart.
+pub struct EngineSession {
+    pub engine: Engine,
+    parser: tree_sitter::Parser,
+    tree: tree_sitter::Tree,
+}
+
+impl EngineSession {
+    pub fn open(engine: Engine) -> Result<Self, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(engine.config.language).map_err(SliceError::TreeSitterVersionError)?;
+
+        let tree = parser.parse(&engine.src, None).unwrap();
+
+        Ok(EngineSession{engine, parser, tree})
+    }
+
+    /// Apply a single edit delta to the session's buffer, mirror it into the cached tree via
+    /// `Tree::edit`, and incrementally reparse with the old tree so only the affected subtree is
+    /// rebuilt.
+    pub fn apply_edit(&mut self, edit: tree_sitter::InputEdit, new_src: String) {
+        self.tree.edit(&edit);
+        self.engine.src = new_src;
+        self.tree = self.parser.parse(&self.engine.src, Some(&self.tree)).unwrap();
+    }
+
+    pub fn slice(&mut self, target_point: tree_sitter::Point, direction: SliceDirection) -> Result<Vec<tree_sitter::Range>, SliceError> {
+        self.engine.slice_on_tree(self.tree.root_node(), target_point, direction)
+    }
+
+    /// Inline, then fold the resulting rewrite back into the cached tree so the session stays
+    /// incremental across repeated inline calls on the same buffer.
+    pub fn inline(&mut self, point: tree_sitter::Point, target_content: &str, target_point: tree_sitter::Point) -> Result<String, SliceError> {
+        let old_src = self.engine.src.clone();
+        let new_src = self.engine.inline_on_tree(&mut self.parser, self.tree.root_node(), point, target_content, target_point)?;
+
+        let edit = edit_for_replacement(&old_src, &new_src);
+        self.apply_edit(edit, new_src.clone());
+
+        Ok(new_src)
+    }
+
+    /// Delete the given ranges, then fold the resulting rewrite back into the cached tree.
+    pub fn delete_ranges(&mut self, ranges: &Vec<tree_sitter::Range>, target_point: tree_sitter::Point) -> tree_sitter::Point {
+        let old_src = self.engine.src.clone();
+        let (new_src, new_target_point) = delete_ranges(&old_src, ranges, target_point);
+
+        let edit = edit_for_replacement(&old_src, &new_src);
+        self.apply_edit(edit, new_src);
+
+        new_target_point
+    }
+}
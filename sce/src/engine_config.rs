@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+
+use crate::guess_language;
+
+extern "C" {
+    pub fn tree_sitter_c() -> tree_sitter::Language;
+    pub fn tree_sitter_cpp() -> tree_sitter::Language;
+    pub fn tree_sitter_c_sharp() -> tree_sitter::Language;
+    pub fn tree_sitter_go() -> tree_sitter::Language;
+    pub fn tree_sitter_java() -> tree_sitter::Language;
+    pub fn tree_sitter_javascript() -> tree_sitter::Language;
+    pub fn tree_sitter_python() -> tree_sitter::Language;
+    pub fn tree_sitter_ruby() -> tree_sitter::Language;
+    pub fn tree_sitter_rust() -> tree_sitter::Language;
+    pub fn tree_sitter_typescript() -> tree_sitter::Language;
+}
+
+/// EngineConfig is the main configuration for the engine.
+/// This includes all language-specific tree-sitter type names which various stages of the engine
+/// need.
+pub struct EngineConfig {
+    /// The tree_sitter language the engine should use to parse with
+    pub language: tree_sitter::Language,
+
+    /// Subtype information from NODE_TYPES
+    pub subtypes: HashMap<String, Vec<String>>,
+
+    /// Type names representing "atomic" name fragments (e.g. `self`, `foo`, `bar`)
+    pub identifier_types: Vec<&'static str>,
+
+    /// Type names representing any possible "complete" name (e.g. `self.foo.bar`)
+    pub name_types: Vec<&'static str>,
+
+    /// Type names representing constants (constant integers, true/false, null, etc.)
+    pub constant_types: Vec<&'static str>,
+
+    /// Type names and the field names for the descendant destination and source representing ways a
+    /// variable can flow into a new variable (e.g. assignment).
+    /// e.g. ("assignment_expression", ("left", "right"))
+    pub propagating_types: Vec<(&'static str, (&'static str, &'static str))>,
+
+    /// Type names representing statements. Can use "inheritance" information from node-types.
+    pub statement_types: Vec<&'static str>,
+
+    /// Type names representing scopes in which we can slice (just functions?)
+    pub slice_scope_types: Vec<&'static str>,
+
+    /// Type names representing a definition that should show up in the file outline (functions,
+    /// methods, classes/structs, ...), paired with the symbol kind label to report for it (e.g.
+    /// "function", "class").
+    pub outline_types: Vec<(&'static str, &'static str)>,
+
+    /// Type names representing variable accessibility "boundaries" in the language, where
+    /// variables defined within are not accessible outside of.
+    /// For Python, this would be function level, but for C-like languages, this would be
+    /// block-level.
+    pub var_definition_scope_types: Vec<&'static str>,
+
+    // In general, the "accuracy" with detecting names and constructs is lower for slicing than it
+    // is for inlining, hence the change to using actual queries below for inlining related things.
+    // https://tree-sitter.github.io/tree-sitter/using-parsers#query-syntax
+
+    /// Type names representing function calls.
+    pub function_call_types: Vec<&'static str>,
+
+    /// The tree-sitter query used to list function definition parameters.
+    /// This should capture the name of parameters as @param_name, and the type of the parameters as @param_type.
+    /// It should also capture the type of the function as @function_type, and the body of the function as @function_body.
+    pub function_query: tree_sitter::Query,
+
+    /// The tree-sitter query used to list function call arguments.
+    /// This should capture the argument's value expression as @value.
+    pub call_args_query: tree_sitter::Query,
+
+    /// The tree-sitter query used to list return expressions.
+    /// This should capture the return statement as @return_statement and the returned value expression as @return_value.
+    pub returns_query: tree_sitter::Query,
+
+    /// The format string used to generate temporary variables.
+    /// e.g. `{type} {name} = {value};`
+    pub temp_var_format: &'static str,
+}
+
+#[derive(Deserialize)]
+struct NodeType {
+    r#type: String,
+    #[serde(default)]
+    subtypes: Vec<NodeType>,
+}
+
+fn expand_node_types(node_types_json: &str) -> HashMap<String, Vec<String>> {
+    let mut subtypes = HashMap::new();
+
+    for node_type in serde_json::from_str::<Vec<NodeType>>(node_types_json).unwrap() {
+        let mut node_subtypes = vec![node_type.r#type.clone()];
+        node_subtypes.extend(node_type.subtypes.iter().map(|t| t.r#type.clone()));
+        subtypes.insert(node_type.r#type, node_subtypes);
+    }
+
+    subtypes
+}
+
+pub fn from_guessed_language(language: guess_language::Language) -> Option<EngineConfig> {
+    use guess_language::Language::*;
+
+    match language {
+        C => {
+            // https://github.com/tree-sitter/tree-sitter-c/blob/master/src/grammar.json
+            Some(EngineConfig{
+                language: unsafe {tree_sitter_c()},
+                subtypes: expand_node_types(include_str!("../vendor/tree-sitter-c/src/node-types.json")),
+                identifier_types: vec!["identifier", "field_identifier"],
+                name_types: vec!["identifier", "field_expression"],
+                constant_types: vec!["null", "true", "false", "number_literal", "string_literal", "character_literal"],
+                propagating_types: vec![
+                    ("assignment_expression", ("left", "right")),
+                    ("init_declarator", ("declarator", "value")),
+                ],
+                statement_types: vec!["_statement", "declaration"],
+                slice_scope_types: vec!["function_definition"],
+                outline_types: vec![("function_definition", "function")],
+                var_definition_scope_types: vec!["compound_statement"],
+                function_call_types: vec!["call_expression"],
+                function_query: tree_sitter::Query::new(unsafe {tree_sitter_c()}, "
+                    (function_definition
+                        type: (_type_specifier) @function_type
+                        declarator: (function_declarator
+                            parameters: (parameter_list
+                                (parameter_declaration
+                                    type: (_type_specifier) @param_type
+                                    declarator: (_declarator) @param_name
+                                )
+                            )
+                        )
+                        body: (compound_statement) @function_body
+                    )").unwrap(),
+                call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_c()}, "
+                    (call_expression
+                        arguments: (argument_list
+                            \"(\"
+                            (_expression) @value
+                            \")\"
+                        )
+                    )").unwrap(),
+                returns_query: tree_sitter::Query::new(unsafe {tree_sitter_c()}, "
+                    (return_statement
+                        (_expression) @return_value
+                    ) @return_statement").unwrap(),
+                temp_var_format: "{type} {name} = {value};",
+            })
+        }
+        _ => None
+    }
+}
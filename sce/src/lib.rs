@@ -0,0 +1,9 @@
+pub mod engine;
+pub mod engine_config;
+pub mod guess_language;
+pub mod render;
+pub mod traverse;
+
+pub mod rpc {
+    tonic::include_proto!("sce");
+}
@@ -1,11 +1,17 @@
 use std::path::Path;
+use std::pin::Pin;
 use std::str::FromStr;
-use tonic::{transport::Server, Request, Response, Status};
+use tokio::sync::mpsc;
+use tokio_stream::{Stream, StreamExt, wrappers::ReceiverStream};
+use tonic::{transport::Server, Request, Response, Status, Streaming};
 
 use sce::guess_language::{Language, guess as guess_language};
 use sce::engine_config::from_guessed_language;
-use sce::engine::Engine;
-use sce::rpc::{Source, SliceRequest, SliceResponse, InlineRequest, InlineResponse};
+use sce::engine::{Engine, EngineSession, SliceError};
+use sce::render::{render, RenderMode};
+use sce::rpc::{Source, SliceRequest, SliceResponse, InlineRequest, InlineResponse, ExtractRequest, ExtractResponse, OutlineRequest, OutlineResponse, Edit, SessionRequest, SessionResponse};
+use sce::rpc::session_request::Request as SessionRequestKind;
+use sce::rpc::session_response::Response as SessionResponseKind;
 use sce::rpc::sce_server::{Sce, SceServer};
 
 fn to_ts(point: &sce::rpc::Point) -> tree_sitter::Point {
@@ -28,6 +34,67 @@ fn to_rpc(range: tree_sitter::Range) -> sce::rpc::Range {
     }
 }
 
+/// `Range`/`Point` over the wire only carry line/column, so reconstructing a `tree_sitter::Range`
+/// (which also wants byte offsets) means walking `src` to find the byte offset of each point.
+fn byte_at_point(src: &str, point: tree_sitter::Point) -> usize {
+    let mut row = 0;
+    let mut col = 0;
+    for (i, c) in src.char_indices() {
+        if row == point.row && col == point.column {
+            return i;
+        }
+        if c == '\n' {
+            row += 1;
+            col = 0;
+        } else {
+            col += 1;
+        }
+    }
+    src.len()
+}
+
+fn to_ts_range(src: &str, range: &sce::rpc::Range) -> tree_sitter::Range {
+    let start_point = to_ts(range.start.as_ref().unwrap());
+    let end_point = to_ts(range.end.as_ref().unwrap());
+
+    tree_sitter::Range {
+        start_byte: byte_at_point(src, start_point),
+        end_byte: byte_at_point(src, end_point),
+        start_point,
+        end_point,
+    }
+}
+
+fn to_rpc_symbol(symbol: sce::engine::Symbol) -> sce::rpc::Symbol {
+    sce::rpc::Symbol {
+        name: symbol.name,
+        kind: symbol.kind.to_string(),
+        range: Some(to_rpc(symbol.range)),
+        children: symbol.children.into_iter().map(to_rpc_symbol).collect(),
+    }
+}
+
+/// Every `SliceError` variant is a recoverable per-request condition (bad cursor, ambiguous
+/// extraction, ...), not a server fault, so they all map to `invalid_argument` rather than
+/// `internal` - except a tree-sitter version mismatch, which is a deployment bug on our end.
+fn to_status(err: SliceError) -> Status {
+    match err {
+        SliceError::TreeSitterVersionError(_) => Status::internal(err.to_string()),
+        SliceError::NoNameAtPointError(_) | SliceError::NoCallAtPointError(_) | SliceError::MultipleExtractOutputsError(_) => Status::invalid_argument(err.to_string()),
+    }
+}
+
+fn to_input_edit(edit: &Edit) -> tree_sitter::InputEdit {
+    tree_sitter::InputEdit {
+        start_byte: edit.start_byte as usize,
+        old_end_byte: edit.old_end_byte as usize,
+        new_end_byte: edit.new_end_byte as usize,
+        start_position: to_ts(edit.start_position.as_ref().unwrap()),
+        old_end_position: to_ts(edit.old_end_position.as_ref().unwrap()),
+        new_end_position: to_ts(edit.new_end_position.as_ref().unwrap()),
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct SCEService {}
 impl SCEService {
@@ -51,11 +118,17 @@ impl Sce for SCEService {
         let req = request.into_inner();
         let direction = req.direction();
         let source = req.source.unwrap();
+        let point = to_ts(&source.point.clone().unwrap());
 
         let mut engine = Self::make_engine(&source);
-        let ranges_to_remove = engine.slice(to_ts(&source.point.unwrap()), direction).unwrap();
+        let to_remove = engine.slice(point, direction).map_err(to_status)?;
 
-        Ok(Response::new(SliceResponse{to_remove: ranges_to_remove.into_iter().map(|r| to_rpc(r)).collect()}))
+        let rendered = req.render.map(|r| {
+            let mode = if r.colored { RenderMode::Colored } else { RenderMode::Plain };
+            render(&engine.src, &to_remove, point, mode)
+        });
+
+        Ok(Response::new(SliceResponse{to_remove: to_remove.into_iter().map(|r| to_rpc(r)).collect(), rendered}))
     }
 
     async fn inline(&self, request: Request<InlineRequest>) -> Result<Response<InlineResponse>, Status> {
@@ -64,10 +137,98 @@ impl Sce for SCEService {
 
         let mut engine = Self::make_engine(&source);
 
-        let content = engine.inline(to_ts(&source.point.unwrap()), &req.target_content, to_ts(&req.target_point.unwrap())).unwrap();
+        let content = engine.inline(to_ts(&source.point.unwrap()), &req.target_content, to_ts(&req.target_point.unwrap())).map_err(to_status)?;
 
         Ok(Response::new(InlineResponse{content}))
     }
+
+    async fn extract(&self, request: Request<ExtractRequest>) -> Result<Response<ExtractResponse>, Status> {
+        let req = request.into_inner();
+        let source = req.source.unwrap();
+        let range = to_ts_range(&source.content, &req.range.unwrap());
+
+        let mut engine = Self::make_engine(&source);
+        let content = engine.extract(range).map_err(to_status)?;
+
+        Ok(Response::new(ExtractResponse{content}))
+    }
+
+    async fn outline(&self, request: Request<OutlineRequest>) -> Result<Response<OutlineResponse>, Status> {
+        let req = request.into_inner();
+        let source = req.source.unwrap();
+
+        let mut engine = Self::make_engine(&source);
+        let symbols = engine.outline().map_err(to_status)?;
+
+        Ok(Response::new(OutlineResponse{symbols: symbols.into_iter().map(to_rpc_symbol).collect()}))
+    }
+
+    type SessionStream = Pin<Box<dyn Stream<Item = Result<SessionResponse, Status>> + Send + 'static>>;
+
+    /// Open an incremental parse session on a single buffer. The client sends one `open` message
+    /// with the initial source, followed by any number of `edit`/`slice`/`inline` messages against
+    /// that same buffer; each edit is folded into the session's cached tree via
+    /// `tree_sitter::InputEdit` so `slice`/`inline` only ever reparse the changed region instead of
+    /// the whole file.
+    async fn session(&self, request: Request<Streaming<SessionRequest>>) -> Result<Response<Self::SessionStream>, Status> {
+        let mut in_stream = request.into_inner();
+        let (tx, rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            let mut session: Option<EngineSession> = None;
+
+            while let Some(req) = in_stream.next().await {
+                let req = match req {
+                    Ok(req) => req,
+                    Err(_) => break,
+                };
+
+                match req.request {
+                    Some(SessionRequestKind::Open(source)) => {
+                        let engine = Self::make_engine(&source);
+                        session = Some(EngineSession::open(engine).unwrap());
+                    }
+                    Some(SessionRequestKind::Edit(edit)) => {
+                        if let Some(session) = session.as_mut() {
+                            session.apply_edit(to_input_edit(&edit), edit.new_src);
+                        }
+                    }
+                    Some(SessionRequestKind::Slice(slice_req)) => {
+                        let Some(session) = session.as_mut() else { continue };
+                        let direction = slice_req.direction();
+                        let point = to_ts(&slice_req.source.unwrap().point.unwrap());
+                        let to_remove = session.slice(point, direction).unwrap();
+
+                        let rendered = slice_req.render.map(|r| {
+                            let mode = if r.colored { RenderMode::Colored } else { RenderMode::Plain };
+                            render(&session.engine.src, &to_remove, point, mode)
+                        });
+
+                        let resp = SessionResponse{response: Some(SessionResponseKind::Slice(
+                            SliceResponse{to_remove: to_remove.into_iter().map(to_rpc).collect(), rendered}
+                        ))};
+                        if tx.send(Ok(resp)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(SessionRequestKind::Inline(inline_req)) => {
+                        let Some(session) = session.as_mut() else { continue };
+                        let point = to_ts(&inline_req.source.unwrap().point.unwrap());
+                        let target_point = to_ts(&inline_req.target_point.unwrap());
+                        let content = session.inline(point, &inline_req.target_content, target_point).unwrap();
+
+                        let resp = SessionResponse{response: Some(SessionResponseKind::Inline(InlineResponse{content}))};
+                        if tx.send(Ok(resp)).await.is_err() {
+                            break;
+                        }
+                    }
+                    None => {}
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
 }
 
 #[tokio::main]
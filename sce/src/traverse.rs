@@ -1,3 +1,104 @@
+use std::collections::VecDeque;
+
+/// An item yielded by `BreadthFirstWalk`: either a visited node, or a boundary marker - emitted
+/// after all of one parent's children have been queued (`SiblingsEnd`), or after an entire BFS
+/// level has been fully consumed (`GenerationEnd`) - for callers doing layer-by-layer analysis or
+/// formatting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit<'a> {
+    Node(tree_sitter::Node<'a>),
+    SiblingsEnd,
+    GenerationEnd,
+}
+
+/// BreadthFirstWalk is the level-order counterpart to `DepthFirstWalk`: a `VecDeque` seeded with
+/// the root, draining one node per `next()` and queuing its children behind whatever's already
+/// queued.
+pub struct BreadthFirstWalk<'a> {
+    queue: VecDeque<tree_sitter::Node<'a>>,
+    pending: VecDeque<Visit<'a>>,
+    current_level_count: usize,
+    next_level_count: usize,
+}
+
+pub fn breadth_first<'a>(node: tree_sitter::Node<'a>) -> BreadthFirstWalk<'a> {
+    let mut queue = VecDeque::new();
+    queue.push_back(node);
+
+    BreadthFirstWalk{
+        queue,
+        pending: VecDeque::new(),
+        current_level_count: 1,
+        next_level_count: 0,
+    }
+}
+
+impl<'a> Iterator for BreadthFirstWalk<'a> {
+    type Item = Visit<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(visit) = self.pending.pop_front() {
+            return Some(visit);
+        }
+
+        let node = self.queue.pop_front()?;
+
+        let mut cursor = node.walk();
+        if cursor.goto_first_child() {
+            loop {
+                self.queue.push_back(cursor.node());
+                self.next_level_count += 1;
+
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+            self.pending.push_back(Visit::SiblingsEnd);
+        }
+
+        self.current_level_count -= 1;
+        if self.current_level_count == 0 {
+            self.pending.push_back(Visit::GenerationEnd);
+            self.current_level_count = self.next_level_count;
+            self.next_level_count = 0;
+        }
+
+        Some(Visit::Node(node))
+    }
+}
+
+impl<'a> BreadthFirstWalk<'a> {
+    /// Adapter filtering out the `SiblingsEnd`/`GenerationEnd` markers, for the common case where
+    /// only the visited nodes themselves (in BFS order) are wanted.
+    pub fn nodes(self) -> impl Iterator<Item = tree_sitter::Node<'a>> {
+        self.filter_map(|visit| match visit {
+            Visit::Node(node) => Some(node),
+            _ => None,
+        })
+    }
+}
+
+/// Ancestors yields a node's parent chain, nearest parent first, up to (but not including) the
+/// tree root's non-existent parent - the inverse of a descending walk, useful for scope
+/// resolution ("what function/class/block encloses this node?") without re-walking from the top.
+pub struct Ancestors<'a> {
+    node: Option<tree_sitter::Node<'a>>,
+}
+
+pub fn ancestors<'a>(node: tree_sitter::Node<'a>) -> Ancestors<'a> {
+    Ancestors{node: node.parent()}
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = tree_sitter::Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.node.take()?;
+        self.node = node.parent();
+        Some(node)
+    }
+}
+
 /// DepthFirstWalk is a small helper to do simple iterations over a tree-sitter node/tree,
 /// implementing Iterator for simple for-in uses, as well as a callback-based traversal function,
 /// useful if you want to/need to not traverse deeper when a specific condition is met.
@@ -47,7 +148,257 @@ impl<'a> Iterator for DepthFirstWalk<'a> {
     }
 }
 
+/// PostOrderWalk yields every node of a subtree *after* all of its descendants - the order
+/// bottom-up transformations need, where a child's result must exist before its parent is
+/// handled. Built on the same `TreeCursor`-based approach as `DepthFirstWalk`: descend greedily to
+/// the leftmost leaf, then alternate between moving to a sibling's leftmost leaf and ascending to
+/// emit a now-fully-processed parent.
+pub struct PostOrderWalk<'a> {
+    root: tree_sitter::Node<'a>,
+    cursor: tree_sitter::TreeCursor<'a>,
+    started: bool,
+    done: bool,
+}
+
+pub fn depth_first_post<'a>(node: tree_sitter::Node<'a>) -> PostOrderWalk<'a> {
+    PostOrderWalk{
+        root: node,
+        cursor: node.walk(),
+        started: false,
+        done: false,
+    }
+}
+
+impl<'a> Iterator for PostOrderWalk<'a> {
+    type Item = tree_sitter::Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if !self.started {
+            self.started = true;
+            while self.cursor.goto_first_child() {}
+            return Some(self.cursor.node());
+        }
+
+        // The node at self.cursor is the one we just emitted; find the next node whose
+        // descendants (if any) are all already emitted.
+        if self.cursor.goto_next_sibling() {
+            while self.cursor.goto_first_child() {}
+            return Some(self.cursor.node());
+        }
+
+        if self.cursor.node() == self.root {
+            self.done = true;
+            return None;
+        }
+
+        self.cursor.goto_parent();
+        if self.cursor.node() == self.root {
+            self.done = true;
+        }
+        Some(self.cursor.node())
+    }
+}
+
+/// PostOrderRevWalk mirrors `PostOrderWalk` but visits each node's children right-to-left before
+/// the node itself - the ordering edit application wants, since rewriting a later byte range first
+/// leaves earlier ranges' offsets untouched. `TreeCursor` has no `goto_previous_sibling`, so unlike
+/// `PostOrderWalk` this is driven by an explicit stack of "enter"/"emit" frames rather than cursor
+/// ascent/descent.
+enum PostOrderRevFrame<'a> {
+    Enter(tree_sitter::Node<'a>),
+    Emit(tree_sitter::Node<'a>),
+}
+
+pub struct PostOrderRevWalk<'a> {
+    stack: Vec<PostOrderRevFrame<'a>>,
+}
+
+pub fn depth_first_post_rev<'a>(node: tree_sitter::Node<'a>) -> PostOrderRevWalk<'a> {
+    PostOrderRevWalk{stack: vec![PostOrderRevFrame::Enter(node)]}
+}
+
+impl<'a> Iterator for PostOrderRevWalk<'a> {
+    type Item = tree_sitter::Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(frame) = self.stack.pop() {
+            match frame {
+                PostOrderRevFrame::Enter(node) => {
+                    self.stack.push(PostOrderRevFrame::Emit(node));
+
+                    let mut cursor = node.walk();
+                    if cursor.goto_first_child() {
+                        let mut children = vec![cursor.node()];
+                        while cursor.goto_next_sibling() {
+                            children.push(cursor.node());
+                        }
+                        // Pushed left-to-right so popping (LIFO) visits them right-to-left.
+                        for child in children {
+                            self.stack.push(PostOrderRevFrame::Enter(child));
+                        }
+                    }
+                }
+                PostOrderRevFrame::Emit(node) => return Some(node),
+            }
+        }
+        None
+    }
+}
+
+/// WithDepths is `DepthFirstWalk` with a depth counter threaded alongside it, for callers who want
+/// depth-aware pre-order iteration (indentation-aware printing, depth-limited scans) without
+/// reaching for the callback-based `traverse_with_depth`.
+pub struct WithDepths<'a> {
+    root: tree_sitter::Node<'a>,
+    cursor: tree_sitter::TreeCursor<'a>,
+    depth: usize,
+    done: bool,
+}
+
+impl<'a> Iterator for WithDepths<'a> {
+    type Item = (usize, tree_sitter::Node<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let node = self.cursor.node();
+        let depth = self.depth;
+
+        if self.cursor.goto_first_child() {
+            self.depth += 1;
+            return Some((depth, node));
+        }
+        if self.cursor.goto_next_sibling() {
+            return Some((depth, node));
+        }
+
+        loop {
+            self.cursor.goto_parent();
+            self.depth -= 1;
+
+            if self.cursor.node() == self.root {
+                self.done = true;
+                return Some((depth, node));
+            }
+            if self.cursor.goto_next_sibling() {
+                return Some((depth, node));
+            }
+        }
+    }
+}
+
+/// Leaves is `DepthFirstWalk` filtered down to nodes with no children - tree-sitter's leaf/token
+/// nodes (identifiers, operators, punctuation) - the natural primitive for token-stream
+/// reconstruction, whitespace/trivia handling, and source-range collection.
+pub struct Leaves<'a> {
+    inner: DepthFirstWalk<'a>,
+}
+
+impl<'a> Iterator for Leaves<'a> {
+    type Item = tree_sitter::Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.by_ref().find(|node| node.child_count() == 0)
+    }
+}
+
+impl<'a> Leaves<'a> {
+    /// Consume the iterator, returning just the number of leaves.
+    pub fn leaf_count(self) -> usize {
+        self.count()
+    }
+}
+
+/// What a `Visitor` hook asks the driving `visit` traversal to do next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recursion {
+    /// Keep walking normally.
+    Continue,
+    /// Only valid from `f_down`: skip this node's subtree (and its `f_up`), but keep walking
+    /// siblings.
+    SkipChildren,
+    /// Unwind and end the entire traversal immediately, from either hook.
+    Stop,
+}
+
+/// A tri-state alternative to `traverse`'s `bool`-returning callback: `f_down`/`f_up` give
+/// symmetric enter/leave hooks (so callers can maintain a scope stack), and `Recursion::Stop` lets
+/// a caller abort the whole walk as soon as it finds what it's looking for, rather than scanning
+/// the rest of the tree.
+pub trait Visitor {
+    fn f_down(&mut self, node: tree_sitter::Node) -> Recursion;
+    fn f_up(&mut self, node: tree_sitter::Node) -> Recursion;
+}
+
+enum VisitFrame<'a> {
+    Enter(tree_sitter::Node<'a>),
+    Leave(tree_sitter::Node<'a>),
+}
+
 impl<'a> DepthFirstWalk<'a> {
+    /// Drive `v` over the subtree, calling `f_down` on entry and `f_up` on leaving every node.
+    pub fn visit<V: Visitor>(&mut self, v: &mut V) {
+        let mut stack = vec![VisitFrame::Enter(self.root)];
+
+        while let Some(frame) = stack.pop() {
+            match frame {
+                VisitFrame::Enter(node) => match v.f_down(node) {
+                    Recursion::Stop => return,
+                    Recursion::SkipChildren => {}
+                    Recursion::Continue => {
+                        stack.push(VisitFrame::Leave(node));
+
+                        let mut cursor = node.walk();
+                        if cursor.goto_first_child() {
+                            let mut children = vec![cursor.node()];
+                            while cursor.goto_next_sibling() {
+                                children.push(cursor.node());
+                            }
+                            for child in children.into_iter().rev() {
+                                stack.push(VisitFrame::Enter(child));
+                            }
+                        }
+                    }
+                },
+                VisitFrame::Leave(node) => {
+                    if v.f_up(node) == Recursion::Stop {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adapter filtering the walk down to leaf nodes only (those where `goto_first_child` would
+    /// fail), without re-descending into each one to check.
+    pub fn leaves(self) -> Leaves<'a> {
+        Leaves{inner: self}
+    }
+
+    /// Snapshot the current node's ancestor chain (nearest parent first), e.g. to answer "what
+    /// encloses this node?" mid-walk without re-walking from the top.
+    pub fn ancestors(&self) -> Vec<tree_sitter::Node<'a>> {
+        ancestors(self.cursor.node()).collect()
+    }
+
+    /// Adapter yielding `(depth, node)` pairs instead of just `node`, depth 0 being the root -
+    /// the `Iterator`-based equivalent of the depth tracked internally by `traverse_with_depth`'s
+    /// on_descent/on_ascent callbacks.
+    pub fn with_depths(self) -> WithDepths<'a> {
+        WithDepths{
+            root: self.root,
+            cursor: self.cursor,
+            depth: 0,
+            done: self.done,
+        }
+    }
+
     /// Call the given cb for each node, skipping any descendants of a given node if the cb returns
     /// false. Additionally, call on_descent when descending down into a new "layer" and on_ascent
     /// when coming back up.
@@ -216,6 +567,301 @@ mod tests {
         ]);
     }
 
+    #[test]
+    /// Test ancestors(), ensuring the parent chain is yielded nearest-parent-first.
+    fn test_ancestors() {
+        let tree = sample_tree();
+
+        let target = depth_first(tree.root_node())
+            .find(|n| n.kind() == "identifier" && n.parent().unwrap().kind() == "binary_operator")
+            .unwrap();
+
+        let kinds: Vec<&str> = ancestors(target).map(|n| n.kind()).collect();
+
+        assert_eq!(kinds, vec![
+                   "binary_operator",
+                   "binary_operator",
+                   "return_statement",
+                   "block",
+                   "function_definition",
+                   "module",
+        ]);
+    }
+
+    struct RecordingVisitor {
+        log: Vec<(&'static str, &'static str)>,
+        skip_children_of: Option<&'static str>,
+        stop_at: Option<&'static str>,
+    }
+
+    impl Visitor for RecordingVisitor {
+        fn f_down(&mut self, node: tree_sitter::Node) -> Recursion {
+            self.log.push(("DOWN", node.kind()));
+
+            if self.stop_at == Some(node.kind()) {
+                return Recursion::Stop;
+            }
+            if self.skip_children_of == Some(node.kind()) {
+                return Recursion::SkipChildren;
+            }
+            Recursion::Continue
+        }
+
+        fn f_up(&mut self, node: tree_sitter::Node) -> Recursion {
+            self.log.push(("UP", node.kind()));
+            Recursion::Continue
+        }
+    }
+
+    #[test]
+    /// Test visit(), ensuring every node gets a symmetric f_down/f_up pair in the right order.
+    fn test_visit_full() {
+        let tree = sample_tree();
+
+        let mut visitor = RecordingVisitor{log: vec![], skip_children_of: None, stop_at: None};
+        depth_first(tree.root_node()).visit(&mut visitor);
+
+        assert_eq!(visitor.log, vec![
+                   ("DOWN", "module"),
+                   ("DOWN", "function_definition"),
+                   ("DOWN", "def"),
+                   ("UP", "def"),
+                   ("DOWN", "identifier"),
+                   ("UP", "identifier"),
+                   ("DOWN", "parameters"),
+                   ("DOWN", "("),
+                   ("UP", "("),
+                   ("DOWN", "identifier"),
+                   ("UP", "identifier"),
+                   ("DOWN", ","),
+                   ("UP", ","),
+                   ("DOWN", "identifier"),
+                   ("UP", "identifier"),
+                   ("DOWN", ","),
+                   ("UP", ","),
+                   ("DOWN", "identifier"),
+                   ("UP", "identifier"),
+                   ("DOWN", ")"),
+                   ("UP", ")"),
+                   ("UP", "parameters"),
+                   ("DOWN", ":"),
+                   ("UP", ":"),
+                   ("DOWN", "block"),
+                   ("DOWN", "return_statement"),
+                   ("DOWN", "return"),
+                   ("UP", "return"),
+                   ("DOWN", "binary_operator"),
+                   ("DOWN", "binary_operator"),
+                   ("DOWN", "identifier"),
+                   ("UP", "identifier"),
+                   ("DOWN", "+"),
+                   ("UP", "+"),
+                   ("DOWN", "identifier"),
+                   ("UP", "identifier"),
+                   ("UP", "binary_operator"),
+                   ("DOWN", "+"),
+                   ("UP", "+"),
+                   ("DOWN", "identifier"),
+                   ("UP", "identifier"),
+                   ("UP", "binary_operator"),
+                   ("UP", "return_statement"),
+                   ("UP", "block"),
+                   ("UP", "function_definition"),
+                   ("UP", "module"),
+        ]);
+    }
+
+    #[test]
+    /// Test that SkipChildren omits a node's subtree (and its own f_up) but keeps walking siblings.
+    fn test_visit_skip_children() {
+        let tree = sample_tree();
+
+        let mut visitor = RecordingVisitor{log: vec![], skip_children_of: Some("parameters"), stop_at: None};
+        depth_first(tree.root_node()).visit(&mut visitor);
+
+        // "parameters" gets a DOWN and, since SkipChildren, no UP and no children - but its
+        // sibling ":" afterwards is still visited normally.
+        assert!(visitor.log.contains(&("DOWN", "parameters")));
+        assert!(!visitor.log.contains(&("UP", "parameters")));
+        assert!(!visitor.log.contains(&("DOWN", "(")));
+        assert!(visitor.log.contains(&("DOWN", ":")));
+    }
+
+    #[test]
+    /// Test that Stop unwinds the whole traversal immediately, visiting nothing afterwards.
+    fn test_visit_stop() {
+        let tree = sample_tree();
+
+        let mut visitor = RecordingVisitor{log: vec![], skip_children_of: None, stop_at: Some("return_statement")};
+        depth_first(tree.root_node()).visit(&mut visitor);
+
+        assert_eq!(visitor.log.last(), Some(&("DOWN", "return_statement")));
+        assert!(!visitor.log.contains(&("DOWN", "return")));
+    }
+
+    #[test]
+    /// Test the leaves() adapter and leaf_count() convenience.
+    fn test_leaves() {
+        let tree = sample_tree();
+
+        let node_kinds: Vec<&str> = depth_first(tree.root_node()).leaves().map(|n| n.kind()).collect();
+
+        assert_eq!(node_kinds, vec![
+                   "def",
+                   "identifier",
+                   "(",
+                   "identifier",
+                   ",",
+                   "identifier",
+                   ",",
+                   "identifier",
+                   ")",
+                   ":",
+                   "return",
+                   "identifier",
+                   "+",
+                   "identifier",
+                   "+",
+                   "identifier",
+        ]);
+
+        assert_eq!(depth_first(tree.root_node()).leaves().leaf_count(), 16);
+    }
+
+    #[test]
+    /// Test the with_depths() adapter, ensuring depth tracks descents/ascents correctly.
+    fn test_with_depths() {
+        let tree = sample_tree();
+
+        let visits: Vec<(usize, &str)> = depth_first(tree.root_node()).with_depths()
+            .map(|(depth, node)| (depth, node.kind())).collect();
+
+        assert_eq!(visits, vec![
+                   (0, "module"),
+                   (1, "function_definition"),
+                   (2, "def"),
+                   (2, "identifier"),
+                   (2, "parameters"),
+                   (3, "("),
+                   (3, "identifier"),
+                   (3, ","),
+                   (3, "identifier"),
+                   (3, ","),
+                   (3, "identifier"),
+                   (3, ")"),
+                   (2, ":"),
+                   (2, "block"),
+                   (3, "return_statement"),
+                   (4, "return"),
+                   (4, "binary_operator"),
+                   (5, "binary_operator"),
+                   (6, "identifier"),
+                   (6, "+"),
+                   (6, "identifier"),
+                   (5, "+"),
+                   (5, "identifier"),
+        ]);
+    }
+
+    #[test]
+    /// Test depth_first_post, ensuring every node is yielded after its descendants.
+    fn test_depth_first_post() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(unsafe {crate::engine_config::tree_sitter_python()}).unwrap();
+        let tree = parser.parse("def f(a): return a", None).unwrap();
+
+        let node_kinds: Vec<&str> = depth_first_post(tree.root_node()).map(|n| n.kind()).collect();
+
+        assert_eq!(node_kinds, vec![
+                   "def",
+                   "identifier",
+                   "(",
+                   "identifier",
+                   ")",
+                   "parameters",
+                   ":",
+                   "return",
+                   "identifier",
+                   "return_statement",
+                   "block",
+                   "function_definition",
+                   "module",
+        ]);
+    }
+
+    #[test]
+    /// Test depth_first_post_rev, ensuring children are visited right-to-left before their parent.
+    fn test_depth_first_post_rev() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(unsafe {crate::engine_config::tree_sitter_python()}).unwrap();
+        let tree = parser.parse("def f(a): return a", None).unwrap();
+
+        let node_kinds: Vec<&str> = depth_first_post_rev(tree.root_node()).map(|n| n.kind()).collect();
+
+        assert_eq!(node_kinds, vec![
+                   "identifier",
+                   "return",
+                   "return_statement",
+                   "block",
+                   ":",
+                   ")",
+                   "identifier",
+                   "(",
+                   "parameters",
+                   "identifier",
+                   "def",
+                   "function_definition",
+                   "module",
+        ]);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum VisitKind {
+        Node(&'static str),
+        SiblingsEnd,
+        GenerationEnd,
+    }
+
+    #[test]
+    /// Test breadth_first, ensuring SiblingsEnd/GenerationEnd are emitted at the right points.
+    fn test_breadth_first() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(unsafe {crate::engine_config::tree_sitter_python()}).unwrap();
+        let tree = parser.parse("def f(a): return a", None).unwrap();
+
+        let visits: Vec<VisitKind> = breadth_first(tree.root_node()).map(|visit| match visit {
+            Visit::Node(node) => VisitKind::Node(node.kind()),
+            Visit::SiblingsEnd => VisitKind::SiblingsEnd,
+            Visit::GenerationEnd => VisitKind::GenerationEnd,
+        }).collect();
+
+        assert_eq!(visits, vec![
+                   VisitKind::Node("module"),
+                   VisitKind::SiblingsEnd,
+                   VisitKind::GenerationEnd,
+                   VisitKind::Node("function_definition"),
+                   VisitKind::SiblingsEnd,
+                   VisitKind::GenerationEnd,
+                   VisitKind::Node("def"),
+                   VisitKind::Node("identifier"),
+                   VisitKind::Node("parameters"),
+                   VisitKind::SiblingsEnd,
+                   VisitKind::Node(":"),
+                   VisitKind::Node("block"),
+                   VisitKind::SiblingsEnd,
+                   VisitKind::GenerationEnd,
+                   VisitKind::Node("("),
+                   VisitKind::Node("identifier"),
+                   VisitKind::Node(")"),
+                   VisitKind::Node("return_statement"),
+                   VisitKind::SiblingsEnd,
+                   VisitKind::GenerationEnd,
+                   VisitKind::Node("return"),
+                   VisitKind::Node("identifier"),
+                   VisitKind::GenerationEnd,
+        ]);
+    }
+
     #[test]
     /// Test traverse_with_depth(cb, on_descent, on_ascent), ensuring descend and ascend get called
     /// as appropriate.
@@ -0,0 +1,134 @@
+//! Bottom-up constant evaluation over a parse tree, in the spirit of the constant-folding clippy
+//! uses for lint accuracy: a node matching `constant_query` maps directly to a `Value`, and an
+//! operator node whose operands all fold to `Value`s folds to a new one. Used during slicing to
+//! substitute known-constant variables and prune branches whose condition is statically known.
+//!
+//! Only C's `binary_expression`/`unary_expression` field layout (`left`/`right`/`argument`, plus
+//! an unnamed operator token) is understood here - the only language wired up today.
+
+use std::collections::HashMap;
+
+use crate::slicer_config::{self, SlicerConfig};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    Str(String),
+    Null,
+}
+
+impl Value {
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            Value::Int(i) => Some(*i != 0),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+/// Fold `node` to a `Value`, given `bindings` for any identifier already known to hold a constant
+/// (see `Slicer::constant_bindings`). Returns `None` when the subtree isn't fully constant, or
+/// when an operator would divide by zero, overflow, or mix incompatible types - folding fails
+/// open (the caller leaves the subtree alone) rather than guessing.
+pub fn eval(config: &SlicerConfig, node: tree_sitter::Node, content: &[u8], bindings: &HashMap<String, Value>) -> Option<Value> {
+    if config.identifier_types.contains(&node.kind()) {
+        return bindings.get(node.utf8_text(content).ok()?).cloned();
+    }
+
+    if slicer_config::node_matches_query(&config.constant_query, "constant", node, content) {
+        return literal_value(node, content);
+    }
+
+    match node.kind() {
+        "parenthesized_expression" => eval(config, node.named_child(0)?, content, bindings),
+        "binary_expression" => {
+            let left = node.child_by_field_name("left")?;
+            let right = node.child_by_field_name("right")?;
+            let operator = operator_token(node, &[left, right])?;
+            let op = operator.utf8_text(content).ok()?;
+
+            // `&&`/`||` short-circuit: a statically-known-false left of `&&` (or known-true left of
+            // `||`) decides the whole expression regardless of whether `right` folds to a constant,
+            // just like the C they're sliced from never evaluates `right` in that case.
+            let left_value = eval(config, left, content, bindings)?;
+            match (op, left_value.as_bool()) {
+                ("&&", Some(false)) => return Some(Value::Bool(false)),
+                ("||", Some(true)) => return Some(Value::Bool(true)),
+                _ => {}
+            }
+
+            fold_binary(op, left_value, eval(config, right, content, bindings)?)
+        }
+        "unary_expression" => {
+            let argument = node.child_by_field_name("argument")?;
+            let operator = operator_token(node, &[argument])?;
+
+            fold_unary(operator.utf8_text(content).ok()?, eval(config, argument, content, bindings)?)
+        }
+        _ => None,
+    }
+}
+
+/// A literal `constant_query` match's own value, based on its node kind.
+fn literal_value(node: tree_sitter::Node, content: &[u8]) -> Option<Value> {
+    let text = node.utf8_text(content).ok()?;
+
+    match node.kind() {
+        "null" => Some(Value::Null),
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        "number_literal" => text.trim_end_matches(|c: char| c.is_ascii_alphabetic()).parse::<i64>().ok().map(Value::Int),
+        "string_literal" | "character_literal" => Some(Value::Str(text.trim_matches(|c| c == '"' || c == '\'').to_string())),
+        _ => None,
+    }
+}
+
+/// The lone child of `node` that isn't one of `operands` - the operator token itself, since
+/// `binary_expression`/`unary_expression` don't give the operator its own field name.
+fn operator_token<'a>(node: tree_sitter::Node<'a>, operands: &[tree_sitter::Node<'a>]) -> Option<tree_sitter::Node<'a>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).find(|child| !operands.contains(child))
+}
+
+fn fold_binary(op: &str, left: Value, right: Value) -> Option<Value> {
+    match op {
+        "&&" => Some(Value::Bool(left.as_bool()? && right.as_bool()?)),
+        "||" => Some(Value::Bool(left.as_bool()? || right.as_bool()?)),
+        "==" | "!=" if std::mem::discriminant(&left) != std::mem::discriminant(&right) => None,
+        "==" => Some(Value::Bool(left == right)),
+        "!=" => Some(Value::Bool(left != right)),
+        _ => {
+            let (left, right) = (left.as_int()?, right.as_int()?);
+            match op {
+                "+" => left.checked_add(right).map(Value::Int),
+                "-" => left.checked_sub(right).map(Value::Int),
+                "*" => left.checked_mul(right).map(Value::Int),
+                "/" if right != 0 => left.checked_div(right).map(Value::Int),
+                "%" if right != 0 => left.checked_rem(right).map(Value::Int),
+                "<" => Some(Value::Bool(left < right)),
+                "<=" => Some(Value::Bool(left <= right)),
+                ">" => Some(Value::Bool(left > right)),
+                ">=" => Some(Value::Bool(left >= right)),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn fold_unary(op: &str, value: Value) -> Option<Value> {
+    match op {
+        "!" => Some(Value::Bool(!value.as_bool()?)),
+        "-" => value.as_int().and_then(|i| i.checked_neg()).map(Value::Int),
+        "+" => value.as_int().map(Value::Int),
+        _ => None,
+    }
+}
@@ -0,0 +1,88 @@
+//! Minimal edits over a source buffer, for driving an editor/LSP client incrementally instead of
+//! diffing a whole rewritten buffer against the original. A `TextEdit` is just `{ start, end,
+//! new_text }` for one changed span; `LineIndex` maps byte offsets (what tree-sitter and the rest
+//! of this crate work in) to/from LSP's UTF-16 `(line, character)` `Position`s.
+
+/// An LSP-style position: a zero-indexed line and a UTF-16 code-unit column within it - unlike
+/// `tree_sitter::Point`, whose column is a byte offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+/// A single minimal change: replace the span from `start` to `end` with `new_text`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub start: Position,
+    pub end: Position,
+    pub new_text: String,
+}
+
+/// Maps byte offsets into a buffer to/from `Position`s. Built once per buffer (by scanning for
+/// `\n`) rather than repeating that scan per lookup; the buffer itself isn't retained; callers
+/// pass the same `src` they built the index from back into `offset_to_position`/
+/// `position_to_offset`.
+pub struct LineIndex {
+    /// Byte offset of the start of each line; line 0 always starts at 0.
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(src: &str) -> LineIndex {
+        let mut line_starts = vec![0];
+        line_starts.extend(src.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex{ line_starts }
+    }
+
+    fn line_of(&self, byte: usize) -> usize {
+        match self.line_starts.binary_search(&byte) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        }
+    }
+
+    fn line_span(&self, src: &str, line: usize) -> std::ops::Range<usize> {
+        let start = self.line_starts[line];
+        let end = self.line_starts.get(line + 1).copied().unwrap_or(src.len());
+        start..end
+    }
+
+    pub fn offset_to_position(&self, src: &str, byte: usize) -> Position {
+        let line = self.line_of(byte);
+        let character = src[self.line_starts[line]..byte].encode_utf16().count();
+        Position{ line, character }
+    }
+
+    pub fn position_to_offset(&self, src: &str, position: Position) -> usize {
+        let span = self.line_span(src, position.line);
+        let line = &src[span.clone()];
+
+        let mut utf16_count = 0;
+        for (byte_offset, ch) in line.char_indices() {
+            if utf16_count >= position.character {
+                return span.start + byte_offset;
+            }
+            utf16_count += ch.len_utf16();
+        }
+        span.end
+    }
+
+    /// Remap a `tree_sitter::Point` (row + byte column) to a `Position` (line + UTF-16 column)
+    /// over the same index, rather than re-scanning the buffer.
+    pub fn point_to_position(&self, src: &str, point: tree_sitter::Point) -> Position {
+        self.offset_to_position(src, self.point_to_offset(point))
+    }
+
+    /// A `tree_sitter::Point`'s byte offset - unlike `position_to_offset`, no UTF-16 counting is
+    /// needed since a `Point`'s column is already a byte offset within its row.
+    pub fn point_to_offset(&self, point: tree_sitter::Point) -> usize {
+        self.line_starts[point.row] + point.column
+    }
+
+    /// The inverse of `point_to_offset`.
+    pub fn offset_to_point(&self, byte: usize) -> tree_sitter::Point {
+        let row = self.line_of(byte);
+        tree_sitter::Point{ row, column: byte - self.line_starts[row] }
+    }
+}
@@ -0,0 +1,145 @@
+//! Recovers tagged ranges and cursor points from annotated test fixtures, the same way editor
+//! test suites pull `<tag>`-delimited spans out of a string instead of making the test author
+//! hand-compute byte offsets or `(row, column)` pairs. `extract_tag` strips a single
+//! `<tag attr="value">...</tag>` span and reports the `tree_sitter::Range` its content occupies
+//! in the *stripped* source (plus whatever attributes were written on the opening tag);
+//! `extract_cursor` strips a single `$0`-style marker and reports the `Point` it sat at. The two
+//! can be chained - strip the cursor out of what `extract_tag` already stripped. Each result is
+//! relative to the source it was extracted from, so when the cursor marker comes before the tag,
+//! the tag's byte offsets (and its columns, on the marker's own line) are off by the marker's
+//! length in the fully stripped source.
+
+use std::collections::HashMap;
+
+use crate::edit::LineIndex;
+
+/// A `<tag attr="value">...</tag>` span recovered from a fixture: the byte range of its content,
+/// in terms of the source with the tag markup already removed, plus the opening tag's attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedRange {
+    pub range: tree_sitter::Range,
+    pub attrs: HashMap<String, String>,
+}
+
+/// Strip the first `<tag ...>...</tag>` span out of `src`, returning the stripped source and the
+/// recovered `TaggedRange`. `None` if `tag` doesn't open and close in `src`.
+pub fn extract_tag(src: &str, tag: &str) -> Option<(String, TaggedRange)> {
+    let open_start = src.find(&format!("<{}", tag))?;
+    let open_end = src[open_start..].find('>')? + open_start + 1;
+    let attrs = parse_attrs(&src[open_start + 1 + tag.len()..open_end - 1]);
+
+    let close_tag = format!("</{}>", tag);
+    let close_start = src[open_end..].find(&close_tag)? + open_end;
+    let close_end = close_start + close_tag.len();
+
+    let mut stripped = String::with_capacity(src.len());
+    stripped += &src[..open_start];
+    stripped += &src[open_end..close_start];
+    stripped += &src[close_end..];
+
+    let content_start = open_start;
+    let content_end = close_start - (open_end - open_start);
+
+    let index = LineIndex::new(&stripped);
+    Some((stripped, TaggedRange{
+        range: tree_sitter::Range{
+            start_byte: content_start,
+            end_byte: content_end,
+            start_point: index.offset_to_point(content_start),
+            end_point: index.offset_to_point(content_end),
+        },
+        attrs,
+    }))
+}
+
+/// Parse a `key="value" key2="value with spaces"` attribute list, as found between a tag's name
+/// and its closing `>`. Values are scanned between quotes rather than split on whitespace, so a
+/// quoted value may itself contain spaces.
+fn parse_attrs(s: &str) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    let mut rest = s;
+
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_string();
+        rest = &rest[eq + 1..];
+
+        let quote_start = match rest.find('"') {
+            Some(i) => i,
+            None => break,
+        };
+        rest = &rest[quote_start + 1..];
+
+        let quote_end = match rest.find('"') {
+            Some(i) => i,
+            None => break,
+        };
+
+        attrs.insert(key, rest[..quote_end].to_string());
+        rest = &rest[quote_end + 1..];
+    }
+
+    attrs
+}
+
+/// Strip the first occurrence of `marker` (e.g. `"$0"`) out of `src`, returning the stripped
+/// source and the `Point` it was found at. `None` if `marker` doesn't appear.
+pub fn extract_cursor(src: &str, marker: &str) -> Option<(String, tree_sitter::Point)> {
+    let offset = src.find(marker)?;
+
+    let mut stripped = String::with_capacity(src.len());
+    stripped += &src[..offset];
+    stripped += &src[offset + marker.len()..];
+
+    let index = LineIndex::new(&stripped);
+    Some((stripped, index.offset_to_point(offset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_tag_strips_markup_and_locates_content() {
+        let (stripped, tagged) = extract_tag("a\n<callsite>square(5)</callsite>;\n", "callsite").unwrap();
+        assert_eq!(stripped, "a\nsquare(5);\n");
+        assert_eq!(tagged.range.start_point, tree_sitter::Point{ row: 1, column: 0 });
+        assert_eq!(tagged.range.end_point, tree_sitter::Point{ row: 1, column: 9 });
+        assert_eq!(&stripped[tagged.range.start_byte..tagged.range.end_byte], "square(5)");
+    }
+
+    #[test]
+    fn extract_tag_recovers_attrs() {
+        let (_, tagged) = extract_tag("<delete reason=\"dead code\">x();</delete>", "delete").unwrap();
+        assert_eq!(tagged.attrs.get("reason").map(String::as_str), Some("dead code"));
+    }
+
+    #[test]
+    fn extract_tag_none_when_tag_absent() {
+        assert_eq!(extract_tag("no tags here", "callsite"), None);
+    }
+
+    #[test]
+    fn extract_cursor_strips_marker_and_locates_point() {
+        let (stripped, point) = extract_cursor("int $0square(int x) {}", "$0").unwrap();
+        assert_eq!(stripped, "int square(int x) {}");
+        assert_eq!(point, tree_sitter::Point{ row: 0, column: 4 });
+    }
+
+    #[test]
+    fn extract_cursor_none_when_marker_absent() {
+        assert_eq!(extract_cursor("nothing to see here", "$0"), None);
+    }
+
+    #[test]
+    fn markers_compose_when_tag_is_extracted_first() {
+        // The callsite tag sits after the $0 cursor in the raw fixture; extracting the tag first
+        // (as the inline fixture harness does) must not invalidate the cursor extracted from what
+        // it left behind.
+        let raw = "$0def f():\n  <callsite>g()</callsite>\n";
+        let (after_tag, tagged) = extract_tag(raw, "callsite").unwrap();
+        let (after_both, point) = extract_cursor(&after_tag, "$0").unwrap();
+        assert_eq!(after_both, "def f():\n  g()\n");
+        assert_eq!(point, tree_sitter::Point{ row: 0, column: 0 });
+        assert_eq!(tagged.range.start_point, tree_sitter::Point{ row: 1, column: 2 });
+    }
+}
@@ -0,0 +1,130 @@
+//! A property-test harness, in the spirit of a reparse-check, that guards the inliner and
+//! `delete_ranges` against producing invalid trees or mislocating `target_point`. `fuzz_delete`
+//! and `fuzz_inline` are meant to be driven by a `cargo-fuzz` `fuzz_target!` (e.g. a
+//! `fuzz/fuzz_targets/delete.rs` calling `slicer::fuzz::fuzz_delete(data)`) or any other input
+//! generator feeding them arbitrary bytes; both assert their invariants via `assert!`/
+//! `assert_eq!`, so a fuzzer's shrinker naturally minimizes and reports the failing input itself.
+//! This is what would have caught the kind of off-by-one latent in the old line-splicing
+//! `delete_ranges` (see `slicer::merge_ranges`) and the `replacement[..replacement.len()-1]`
+//! truncation in `Slicer::build_inline_replacement`.
+
+use crate::config_loader;
+use crate::edit::LineIndex;
+use crate::slicer::{self, Slicer};
+use crate::traverse::depth_first;
+
+/// Count of tree-sitter ERROR nodes under `root`.
+fn error_count(root: tree_sitter::Node) -> usize {
+    let mut count = 0;
+    depth_first(root).traverse(|node| {
+        if node.is_error() {
+            count += 1;
+        }
+        true
+    });
+    count
+}
+
+/// Parse `src` as C, returning its tree and ERROR node count.
+fn parse_c(src: &str) -> (tree_sitter::Tree, usize) {
+    let config = config_loader::from_default_config("c").unwrap();
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(config.language).unwrap();
+    let tree = parser.parse(src, None).unwrap();
+    let errors = error_count(tree.root_node());
+    (tree, errors)
+}
+
+/// Property-test `delete_ranges`. `data`'s first line is `"<offset> <len>"` (clamped into
+/// bounds); the rest is wrapped in a minimal `void f(void) { ... }` C function and used as both
+/// the deleted span and the source for `target_point`. Malformed input is simply skipped (not a
+/// finding - there's no transform to check), same as any fuzz harness discarding unusable inputs.
+pub fn fuzz_delete(data: &[u8]) {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+    let (header, body) = match text.split_once('\n') {
+        Some(parts) => parts,
+        None => return,
+    };
+    let (offset_str, len_str) = match header.split_once(' ') {
+        Some(parts) => parts,
+        None => return,
+    };
+    let (offset, len) = match (offset_str.parse::<usize>(), len_str.parse::<usize>()) {
+        (Ok(offset), Ok(len)) => (offset, len),
+        _ => return,
+    };
+
+    let src = format!("void f(void) {{\n{}\n}}\n", body);
+    let offset = offset.min(src.len());
+    let len = len.min(src.len() - offset);
+
+    let index = LineIndex::new(&src);
+    let range = tree_sitter::Range{
+        start_byte: offset,
+        end_byte: offset + len,
+        start_point: index.offset_to_point(offset),
+        end_point: index.offset_to_point(offset + len),
+    };
+    // Target a point just past the deleted span, so it's never itself deleted.
+    let target_point = index.offset_to_point(offset + len);
+
+    let (before_tree, before_errors) = parse_c(&src);
+    let before_kind = before_tree.root_node().descendant_for_point_range(target_point, target_point).map(|n| n.kind());
+
+    let (after_src, after_target_point) = slicer::delete_ranges(&src, &vec![range], target_point);
+
+    let (after_tree, after_errors) = parse_c(&after_src);
+    assert!(after_errors <= before_errors, "delete_ranges introduced new ERROR nodes: {:?}", after_src);
+
+    let after_kind = after_tree.root_node().descendant_for_point_range(after_target_point, after_target_point).map(|n| n.kind());
+    assert_eq!(before_kind, after_kind, "target_point no longer resolves to the same node kind: {:?}", after_src);
+
+    // Every byte not inside the deleted range appears in the output, in the same relative order.
+    let expected: String = src[..offset].chars().chain(src[offset + len..].chars()).collect();
+    assert_eq!(after_src, expected, "deleted output doesn't preserve surrounding bytes in order");
+}
+
+/// Property-test the inliner. `data`'s first line is unused (kept so both harnesses share the
+/// same "first line is the operation, rest is source" shape); the rest is wrapped in
+/// `target(void) { <body> }` plus a `caller(void) { target(); }` and the call is inlined. Checks
+/// that inlining adds no ERROR nodes and that `target_point` still resolves to the same node
+/// kind - the byte-preservation invariant `fuzz_delete` checks is specific to
+/// `delete_ranges` and doesn't apply here, since inlining legitimately rewrites text.
+pub fn fuzz_inline(data: &[u8]) {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+    let body = match text.split_once('\n') {
+        Some((_, body)) => body,
+        None => return,
+    };
+
+    let src = format!("void target(void) {{\n{}\n}}\nvoid caller(void) {{\n  target();\n}}\n", body);
+    let target_point = tree_sitter::Point{ row: 0, column: 0 };
+
+    let call_offset = match src.rfind("target();") {
+        Some(offset) => offset,
+        None => return,
+    };
+    let call_point = LineIndex::new(&src).offset_to_point(call_offset);
+
+    let (before_tree, before_errors) = parse_c(&src);
+    let before_kind = before_tree.root_node().descendant_for_point_range(target_point, target_point).map(|n| n.kind());
+
+    let config = config_loader::from_default_config("c").unwrap();
+    let mut slicer = Slicer{ config, src: src.clone() };
+    let after_src = match slicer.inline(call_point, &src, target_point) {
+        Ok(after_src) => after_src,
+        Err(_) => return,
+    };
+
+    let (after_tree, after_errors) = parse_c(&after_src);
+    assert!(after_errors <= before_errors, "inline introduced new ERROR nodes: {:?}", after_src);
+
+    let after_kind = after_tree.root_node().descendant_for_point_range(target_point, target_point).map(|n| n.kind());
+    assert_eq!(before_kind, after_kind, "target_point no longer resolves to the same node kind after inline: {:?}", after_src);
+}
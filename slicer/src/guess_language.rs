@@ -0,0 +1,112 @@
+//! Near-verbatim copy of `sce/src/guess_language.rs` (frozen since `slicer` forked off `sce` - see
+//! the note atop `sce/src/engine.rs`) - mirror any fix made to one copy into the other.
+
+use std::path::Path;
+use std::str::FromStr;
+
+/// The set of languages `guess` can name. `slicer_config::from_guessed_language` builds a
+/// `SlicerConfig` only for those with a table in `config/languages.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    C,
+    CPlusPlus,
+    CSharp,
+    Go,
+    Java,
+    JavaScript,
+    Python,
+    Ruby,
+    Rust,
+    TypeScript,
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Language::*;
+
+        match s {
+            "c" => Ok(C),
+            "cpp" | "c++" => Ok(CPlusPlus),
+            "csharp" | "c#" => Ok(CSharp),
+            "go" => Ok(Go),
+            "java" => Ok(Java),
+            "javascript" | "js" => Ok(JavaScript),
+            "python" => Ok(Python),
+            "ruby" => Ok(Ruby),
+            "rust" => Ok(Rust),
+            "typescript" | "ts" => Ok(TypeScript),
+            _ => Err(format!("unknown language {:?}", s)),
+        }
+    }
+}
+
+/// Guess the `Language` of a file from its extension, falling back to content sniffing (a `#!`
+/// shebang, then lightweight token heuristics) when the extension is missing, unrecognized, or -
+/// in the case of `.h` - ambiguous between C and C++.
+pub fn guess(path: &Path, content: &str) -> Option<Language> {
+    use Language::*;
+
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        match ext {
+            "h" => return Some(guess_c_or_cpp(content)),
+            "c" => return Some(C),
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" => return Some(CPlusPlus),
+            "cs" => return Some(CSharp),
+            "go" => return Some(Go),
+            "java" => return Some(Java),
+            "js" | "jsx" | "mjs" => return Some(JavaScript),
+            "py" => return Some(Python),
+            "rb" => return Some(Ruby),
+            "rs" => return Some(Rust),
+            "ts" | "tsx" => return Some(TypeScript),
+            _ => {} // unrecognized extension (or none) - fall through to content sniffing
+        }
+    }
+
+    guess_from_shebang(content).or_else(|| guess_from_heuristics(content))
+}
+
+/// `.h` headers are ambiguous between C and C++; look for a few tokens that only show up in C++.
+fn guess_c_or_cpp(content: &str) -> Language {
+    const CPP_MARKERS: [&str; 5] = ["class ", "namespace ", "template<", "template <", "public:"];
+
+    if CPP_MARKERS.iter().any(|marker| content.contains(marker)) {
+        Language::CPlusPlus
+    } else {
+        Language::C
+    }
+}
+
+/// Parse a leading `#!` shebang line, mapping its interpreter (following `env`, if present) to a
+/// `Language`. Handles both `#!/usr/bin/python3` and `#!/usr/bin/env python3` forms.
+fn guess_from_shebang(content: &str) -> Option<Language> {
+    let shebang = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = shebang.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.next()?;
+    }
+
+    if interpreter.starts_with("python") {
+        Some(Language::Python)
+    } else if interpreter.starts_with("ruby") {
+        Some(Language::Ruby)
+    } else if interpreter == "node" || interpreter == "nodejs" {
+        Some(Language::JavaScript)
+    } else {
+        None
+    }
+}
+
+/// Last-resort token heuristics for extensionless/piped sources with no shebang.
+fn guess_from_heuristics(content: &str) -> Option<Language> {
+    if ["interface ", ": string", ": number", "as const"].iter().any(|t| content.contains(t)) {
+        Some(Language::TypeScript)
+    } else if ["function ", "const ", "=>", "require("].iter().any(|t| content.contains(t)) {
+        Some(Language::JavaScript)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,12 @@
+pub mod config_loader;
+pub mod constant_eval;
+pub mod edit;
+pub mod fixture;
+pub mod fuzz;
+pub mod guess_language;
+pub mod render;
+pub mod resolver;
+pub mod slicer;
+pub mod slicer_config;
+pub mod traverse;
+pub mod type_infer;
@@ -3,6 +3,7 @@ use std::str::FromStr;
 use serde::{Deserialize, Serialize};
 
 use slicer::guess_language::{Language, guess as guess_language};
+use slicer::render::{render, RenderMode};
 use slicer::slicer_config::from_guessed_language;
 use slicer::slicer::{Slicer, SliceDirection};
 
@@ -29,11 +30,53 @@ impl From<tree_sitter::Range> for SerializableRange {
         SerializableRange((SerializablePoint::from(range.start_point), SerializablePoint::from(range.end_point)))
     }
 }
+impl SerializableRange {
+    /// `SerializableRange` only carries (line, column) points, so recovering byte offsets (which
+    /// `tree_sitter::Range` also wants) means walking `src` to find them.
+    fn into_ts_range(self, src: &str) -> tree_sitter::Range {
+        let start_point: tree_sitter::Point = self.0.0.into();
+        let end_point: tree_sitter::Point = self.0.1.into();
+
+        let byte_at_point = |point: tree_sitter::Point| -> usize {
+            let mut row = 0;
+            let mut col = 0;
+            for (i, c) in src.char_indices() {
+                if row == point.row && col == point.column {
+                    return i;
+                }
+                if c == '\n' {
+                    row += 1;
+                    col = 0;
+                } else {
+                    col += 1;
+                }
+            }
+            src.len()
+        };
+
+        tree_sitter::Range {
+            start_byte: byte_at_point(start_point),
+            end_byte: byte_at_point(end_point),
+            start_point,
+            end_point,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+/// How (if at all) the caller wants the slice rendered as a human-readable annotated snippet,
+/// rather than just the raw `ranges_to_remove`.
+struct RenderRequest {
+    /// Whether to emit ANSI color/dim escapes, or plain-text markers only.
+    colored: bool,
+}
 
 #[derive(Deserialize)]
 struct SliceRequest {
     /// The direction of the slice (forward or backward)
     direction: SliceDirection,
+    /// If present, also render the slice as an annotated snippet (see `SliceResponse::rendered`).
+    render: Option<RenderRequest>,
 }
 
 #[derive(Deserialize)]
@@ -48,10 +91,19 @@ struct InlineRequest {
     target_point: SerializablePoint,
 }
 
+#[derive(Deserialize)]
+/// Request to extract the statements spanning `range` into a new function.
+struct ExtractRequest {
+    /// The span of statements to hoist into a new function.
+    range: SerializableRange,
+}
+
 #[derive(Deserialize)]
 enum RequestOperation{
     Slice,
     Inline,
+    Extract,
+    Outline,
 }
 
 #[derive(Deserialize)]
@@ -65,10 +117,11 @@ struct Request {
     /// The point of the cursor in the file.
     point: SerializablePoint,
 
-    /// The desired operation, slice or inline.
+    /// The desired operation: slice, inline, extract, or outline.
     operation: RequestOperation,
     slice: Option<SliceRequest>,
     inline: Option<InlineRequest>,
+    extract: Option<ExtractRequest>,
 }
 
 
@@ -76,6 +129,8 @@ struct Request {
 struct SliceResponse {
     /// The list of ranges which should be removed/hidden to show the slice.
     ranges_to_remove: Vec<SerializableRange>,
+    /// Present when the request asked for `render`: the slice rendered as an annotated snippet.
+    rendered: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -84,6 +139,38 @@ struct InlineResponse {
     content: String,
 }
 
+#[derive(Serialize)]
+struct ExtractResponse {
+    /// The full content of the file with the given range hoisted into a new function and
+    /// replaced with a call to it.
+    content: String,
+}
+
+#[derive(Serialize)]
+/// A single definition (function, method, class, ...) found in the file, along with whatever
+/// other definitions are nested within it.
+struct SerializableSymbol {
+    name: String,
+    kind: &'static str,
+    range: SerializableRange,
+    children: Vec<SerializableSymbol>,
+}
+impl From<slicer::slicer::Symbol> for SerializableSymbol {
+    fn from(symbol: slicer::slicer::Symbol) -> Self {
+        SerializableSymbol {
+            name: symbol.name,
+            kind: symbol.kind,
+            range: SerializableRange::from(symbol.range),
+            children: symbol.children.into_iter().map(SerializableSymbol::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OutlineResponse {
+    symbols: Vec<SerializableSymbol>,
+}
+
 fn main() {
     env_logger::init();
 
@@ -102,10 +189,19 @@ fn main() {
 
     match req.operation {
         RequestOperation::Slice => {
-            let ranges_to_remove = slicer.slice(req.point.into(), req.slice.unwrap().direction).unwrap();
+            let slice_req = req.slice.unwrap();
+            let point: tree_sitter::Point = SerializablePoint(req.point.0).into();
+
+            let ranges_to_remove = slicer.slice(point, slice_req.direction).unwrap();
+
+            let rendered = slice_req.render.map(|r| {
+                let mode = if r.colored { RenderMode::Colored } else { RenderMode::Plain };
+                render(&slicer.src, &ranges_to_remove, point, mode)
+            });
 
             serde_json::to_writer(std::io::stdout(), &SliceResponse{
                 ranges_to_remove: ranges_to_remove.into_iter().map(|r| SerializableRange::from(r)).collect(),
+                rendered,
             }).unwrap();
         }
         RequestOperation::Inline => {
@@ -116,5 +212,26 @@ fn main() {
                 content: content,
             }).unwrap();
         }
+        RequestOperation::Extract => {
+            let range = req.extract.unwrap().range.into_ts_range(&slicer.src);
+            let content = match slicer.extract(range) {
+                Ok(content) => content,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            };
+
+            serde_json::to_writer(std::io::stdout(), &ExtractResponse{
+                content: content,
+            }).unwrap();
+        }
+        RequestOperation::Outline => {
+            let symbols = slicer.outline().unwrap();
+
+            serde_json::to_writer(std::io::stdout(), &OutlineResponse{
+                symbols: symbols.into_iter().map(SerializableSymbol::from).collect(),
+            }).unwrap();
+        }
     }
 }
@@ -0,0 +1,102 @@
+//! Near-verbatim copy of `sce/src/render.rs` (frozen since `slicer` forked off `sce` - see the
+//! note atop `sce/src/engine.rs`) - mirror any fix made to one copy into the other.
+
+use std::collections::HashMap;
+
+/// Whether `render` should emit ANSI escapes for dimming/underlining, or just plain text markers
+/// (for callers piping output somewhere that doesn't understand escape codes).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    Plain,
+    Colored,
+}
+
+const DIM_START: &str = "\x1b[2m";
+const DIM_END: &str = "\x1b[0m";
+const UNDERLINE_START: &str = "\x1b[1;33m";
+const UNDERLINE_END: &str = "\x1b[0m";
+
+fn wrap(mode: RenderMode, start: &str, end: &str, text: &str) -> String {
+    match mode {
+        RenderMode::Colored => format!("{}{}{}", start, text, end),
+        RenderMode::Plain => text.to_string(),
+    }
+}
+
+/// Render `src` as a compiler-diagnostic-style annotated snippet: the ranges removed/irrelevant
+/// to a slice are dimmed (or, in `Plain` mode, left as-is but still elided when an entire line is
+/// removed), `target_point` is marked by a `^` caret on the line below it (underlined in
+/// `Colored` mode), and each line is prefixed with its line number in a gutter; columns are not
+/// labeled. Runs of fully-removed lines are folded into a single `N lines elided`
+/// marker rather than being printed individually.
+pub fn render(src: &str, ranges: &[tree_sitter::Range], target_point: tree_sitter::Point, mode: RenderMode) -> String {
+    let lines: Vec<&str> = src.split('\n').collect();
+
+    // Per-row list of (start_col, end_col) spans to dim, and the set of rows entirely removed.
+    let mut row_spans: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    let mut fully_removed: Vec<bool> = vec![false; lines.len()];
+
+    for range in ranges {
+        for row in range.start_point.row..=range.end_point.row.min(lines.len().saturating_sub(1)) {
+            let line_len = lines[row].len();
+            let start_col = if row == range.start_point.row { range.start_point.column } else { 0 };
+            let end_col = if row == range.end_point.row { range.end_point.column } else { line_len };
+
+            row_spans.entry(row).or_default().push((start_col, end_col));
+            if start_col == 0 && end_col >= line_len {
+                fully_removed[row] = true;
+            }
+        }
+    }
+
+    let gutter_width = lines.len().to_string().len();
+    let mut out = String::new();
+    let mut row = 0;
+
+    while row < lines.len() {
+        if fully_removed[row] {
+            let fold_start = row;
+            while row < lines.len() && fully_removed[row] {
+                row += 1;
+            }
+            let count = row - fold_start;
+            out += &format!(
+                "{:>width$} | {}\n",
+                "",
+                wrap(mode, DIM_START, DIM_END, &format!("── {} line{} elided ──", count, if count == 1 { "" } else { "s" })),
+                width = gutter_width,
+            );
+            continue;
+        }
+
+        let line = lines[row];
+        out += &format!("{:>width$} | ", row + 1, width = gutter_width);
+
+        if let Some(spans) = row_spans.get(&row) {
+            let mut col = 0;
+            for &(start, end) in spans {
+                out += &line[col.min(line.len())..start.min(line.len())];
+                out += &wrap(mode, DIM_START, DIM_END, &line[start.min(line.len())..end.min(line.len())]);
+                col = end;
+            }
+            out += &line[col.min(line.len())..];
+        } else {
+            out += line;
+        }
+        out += "\n";
+
+        if row == target_point.row {
+            out += &format!(
+                "{:>width$} | {}{}\n",
+                "",
+                " ".repeat(target_point.column),
+                wrap(mode, UNDERLINE_START, UNDERLINE_END, "^"),
+                width = gutter_width,
+            );
+        }
+
+        row += 1;
+    }
+
+    out
+}
@@ -0,0 +1,67 @@
+//! Scope-aware identifier resolution: walks the parse tree to build a scope tree from
+//! `var_definition_scope_types` and resolves every `identifier_types` occurrence to the nearest
+//! lexically-enclosing declaration - the same name-resolution model rust-analyzer uses in its
+//! body/scope resolver. This replaces matching identifiers by bare name, which can't tell apart
+//! same-named variables in different (or shadowing) scopes.
+
+use std::collections::HashMap;
+
+use crate::slicer_config::{self, SlicerConfig};
+use crate::traverse::depth_first;
+
+/// The declaration an identifier occurrence resolves to, identified by that declaration
+/// identifier node's own tree-sitter node id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DefId(pub usize);
+
+/// Resolve every `identifier_types` node under `root` to the `DefId` of its nearest
+/// lexically-enclosing declaration.
+///
+/// A node is a declaration if it's captured as `@dest` by `propagating_query` (the same notion of
+/// "assignment/declaration" `Slicer::constant_bindings` uses); every other `identifier_types`
+/// occurrence is a use. Declarations are recorded into the current scope as the walk reaches them
+/// in source order, so a use that textually precedes its own scope's (re)declaration doesn't see
+/// the not-yet-recorded inner binding and resolves to whatever an enclosing scope bound instead -
+/// which is also what makes a later declaration in the same or an inner scope shadow an earlier
+/// one, since it simply overwrites that scope's entry for the name.
+///
+/// A child scope opens on every node whose kind is in `var_definition_scope_types` and closes when
+/// that node's subtree is done being walked.
+pub fn resolve<'a>(config: &SlicerConfig, root: tree_sitter::Node<'a>, content: &[u8]) -> HashMap<usize, DefId> {
+    let declarations: std::collections::HashSet<usize> = slicer_config::query_captures(&config.propagating_query, ["dest", "source"], root, content)
+        .into_iter()
+        .map(|[dest, _]| dest.id())
+        .collect();
+
+    let mut resolved = HashMap::new();
+    let mut scopes: Vec<HashMap<String, DefId>> = vec![HashMap::new()];
+
+    depth_first(root).traverse_with_depth(
+        |node| {
+            if config.identifier_types.contains(&node.kind()) {
+                if let Ok(name) = node.utf8_text(content) {
+                    if declarations.contains(&node.id()) {
+                        let def_id = DefId(node.id());
+                        scopes.last_mut().unwrap().insert(name.to_string(), def_id);
+                        resolved.insert(node.id(), def_id);
+                    } else if let Some(def_id) = scopes.iter().rev().find_map(|scope| scope.get(name)) {
+                        resolved.insert(node.id(), *def_id);
+                    }
+                }
+            }
+            true
+        },
+        |parent, _child| {
+            if config.var_definition_scope_types.contains(&parent.kind()) {
+                scopes.push(HashMap::new());
+            }
+        },
+        |_left, parent| {
+            if config.var_definition_scope_types.contains(&parent.kind()) {
+                scopes.pop();
+            }
+        },
+    );
+
+    resolved
+}
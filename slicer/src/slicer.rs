@@ -1,3 +1,9 @@
+//! `slicer` forked off of `sce::engine` (still frozen at that fork point) and has since diverged
+//! substantially, but the core dataflow primitives `extract` leans on - free-variable collection,
+//! `is_defined_before`/`is_used_after`, the single-out-var restriction - are still shared in spirit
+//! with `sce::engine::Engine::extract`. See the note atop `sce/src/engine.rs`: a correctness fix to
+//! either `extract` is worth checking against the other.
+
 use serde::Deserialize;
 use std::cell::RefCell;
 use std::collections::{HashSet, HashMap};
@@ -6,6 +12,10 @@ use thiserror::Error;
 
 use crate::traverse::depth_first;
 use crate::slicer_config::SlicerConfig;
+use crate::constant_eval::{self, Value};
+use crate::resolver::{self, DefId};
+use crate::type_infer;
+use crate::edit;
 
 /// Represents a symbol name, represented as the list of components which make up the symbol
 /// e.g. ["self", "foo", "bar"] in the case of `self.foo.bar` in Python.
@@ -69,6 +79,10 @@ pub enum SliceError {
     NoNameAtPointError(tree_sitter::Point),
     #[error("No call at point {0}")]
     NoCallAtPointError(tree_sitter::Point),
+    #[error("inline sites at bytes {0}..{1} and {2}..{3} overlap")]
+    OverlappingInlineSitesError(usize, usize, usize, usize),
+    #[error("extract: span writes to multiple variables still used afterwards ({0:?}), but only a single return value is supported")]
+    MultipleExtractOutputsError(Vec<String>),
 }
 
 pub struct Slicer {
@@ -76,6 +90,16 @@ pub struct Slicer {
     pub src: String,
 }
 
+/// A single entry in a file's outline: a named definition (function, method, class, ...) along
+/// with whatever other definitions are nested within it.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: &'static str,
+    pub range: tree_sitter::Range,
+    pub children: Vec<Symbol>,
+}
+
 #[derive(Debug)]
 enum RewriteValue<'a> {
     None,
@@ -84,8 +108,10 @@ enum RewriteValue<'a> {
 }
 
 impl Slicer {
-    fn contains_subtype(&self, types: &Vec<&'static str>, node: &tree_sitter::Node) -> bool {
-        types.iter().any(|t| self.config.subtypes[&t.to_string()].contains(&node.kind().to_string()))
+    /// Whether `node` itself is captured as `capture_name` by `query`, i.e. `node` is a match for
+    /// one of the query's alternatives (not just some node within `node`'s subtree).
+    fn node_matches_query(&self, query: &tree_sitter::Query, capture_name: &str, node: tree_sitter::Node, content: &[u8]) -> bool {
+        crate::slicer_config::node_matches_query(query, capture_name, node, content)
     }
 
     /// Return a Vec of all "name components", e.g. ["self", "foo", "bar"]
@@ -120,6 +146,24 @@ impl Slicer {
         Some(NameRef{node, components: self.name_components(&node)})
     }
 
+    /// The first `identifier_types` descendant of `node` - its "base" identifier, e.g. `self` in
+    /// `self.foo.bar` - which is what `resolver::resolve` keys its `DefId`s by.
+    fn base_identifier<'a>(&self, node: &tree_sitter::Node<'a>) -> Option<tree_sitter::Node<'a>> {
+        crate::slicer_config::base_identifier(&self.config.identifier_types, *node)
+    }
+
+    /// Whether `a` and `b` refer to the same binding. Prefers `resolved` (a scope-aware
+    /// `resolver::resolve` map) when both names resolve to a known declaration, since that
+    /// correctly tells apart same-named variables in different/shadowing scopes; falls back to
+    /// `NameRef::affects`'s plain name-prefix comparison when either side isn't in `resolved` (a
+    /// name resolver doesn't recognize, e.g. a global or a construct `propagating_query` misses).
+    fn same_binding(&self, resolved: &HashMap<usize, DefId>, a: &NameRef, b: &NameRef) -> bool {
+        match (self.base_identifier(&a.node).and_then(|n| resolved.get(&n.id())), self.base_identifier(&b.node).and_then(|n| resolved.get(&n.id()))) {
+            (Some(def_a), Some(def_b)) => def_a == def_b,
+            _ => a.affects(b),
+        }
+    }
+
     /// List all names referenced by this node or any descendant.
     fn referenced_names<'a>(&self, node: tree_sitter::Node<'a>) -> Vec<NameRef<'a>> {
         let mut names = vec![];
@@ -137,47 +181,43 @@ impl Slicer {
     fn propagate_targets<'a>(&self, outer_scope: &'a tree_sitter::Node, initial_target_names: &HashSet<NameRef<'a>>, direction: SliceDirection) -> HashSet<NameRef<'a>> {
         let mut target_names = initial_target_names.clone();
 
-        // TODO: use depth_first.traverse_with_depth to push and pop scopes based on
-        // var_definition_scope_types
-        loop {
-            let len_before = target_names.len();
+        // Every dest/source pair the query finds in the scope. Constructs like Python's `with`,
+        // which may or may not define a variable, simply don't match the query's dest field and
+        // never show up here - no extra guard needed.
+        let propagating_pairs = self.get_captures(&self.config.propagating_query, ["dest", "source"], *outer_scope, self.src.as_bytes());
 
-            for descendant in depth_first(*outer_scope) {
-                if let Some((_, (defs_child_name, refs_child_name))) = self.config.propagating_types.iter().find(|&&(expr_kind, (_, _))| expr_kind == descendant.kind()) {
-                    let defs_node = descendant.child_by_field_name(defs_child_name);
-                    let refs_node = descendant.child_by_field_name(refs_child_name);
+        // Scope-aware: two identically-named variables in different (or shadowing) scopes are
+        // different bindings, so propagation shouldn't treat a use of one as a use of the other.
+        let resolved = resolver::resolve(&self.config, *outer_scope, self.src.as_bytes());
 
-                    // Guard against things like python's `with` which may or may not define
-                    // variable(s)
-                    if defs_node.is_none() || refs_node.is_none() {
-                        continue;
-                    }
+        loop {
+            let len_before = target_names.len();
 
-                    let node_defs_names = self.referenced_names(defs_node.unwrap());
-                    let node_refs_names = self.referenced_names(refs_node.unwrap());
-                    log::debug!("defs {:?} refs {:?}", node_defs_names, node_refs_names);
-
-                    match direction {
-                        SliceDirection::Backward => {
-                            // if any known target is used in a defs, all refss in the
-                            // assign should now be targets
-                            if target_names.iter().any(|tname| node_defs_names.iter().any(|dname| tname.affects(&dname))) {
-                                log::info!("Propagating node {:?} adds {:?} to targets", descendant, node_refs_names);
-                                target_names.extend(node_refs_names.clone());
-                            }
-                        },
-                        SliceDirection::Forward => {
-                            // opposite: if any known target is used in a refs, all defss
-                            // should be targets.
-                            if target_names.iter().any(|tname| node_refs_names.iter().any(|sname| tname.affects(&sname))) {
-                                log::info!("Propagating node {:?} adds {:?} to targets", descendant, node_defs_names);
-                                target_names.extend(node_defs_names.clone());
-                            }
-                        },
-                    }
+            for [defs_node, refs_node] in &propagating_pairs {
+                let node_defs_names = self.referenced_names(*defs_node);
+                let node_refs_names = self.referenced_names(*refs_node);
+                log::debug!("defs {:?} refs {:?}", node_defs_names, node_refs_names);
+
+                match direction {
+                    SliceDirection::Backward => {
+                        // if any known target is used in a defs, all refss in the
+                        // assign should now be targets
+                        if target_names.iter().any(|tname| node_defs_names.iter().any(|dname| self.same_binding(&resolved, tname, dname))) {
+                            log::info!("Propagating {:?} -> {:?} adds {:?} to targets", defs_node, refs_node, node_refs_names);
+                            target_names.extend(node_refs_names.clone());
+                        }
+                    },
+                    SliceDirection::Forward => {
+                        // opposite: if any known target is used in a refs, all defss
+                        // should be targets.
+                        if target_names.iter().any(|tname| node_refs_names.iter().any(|sname| self.same_binding(&resolved, tname, sname))) {
+                            log::info!("Propagating {:?} -> {:?} adds {:?} to targets", defs_node, refs_node, node_defs_names);
+                            target_names.extend(node_defs_names.clone());
+                        }
+                    },
                 }
             }
-            
+
             if target_names.len() == len_before {
                 break;
             }
@@ -224,7 +264,7 @@ impl Slicer {
         );
 
         depth_first(target_func).traverse(|statement| {
-            if !self.contains_subtype(&self.config.statement_types, &statement) {
+            if !self.node_matches_query(&self.config.statement_query, "statement", statement, self.src.as_bytes()) {
                 return true;
             }
 
@@ -380,30 +420,84 @@ impl Slicer {
         target_names = self.propagate_targets(&target_func, &target_names, direction);
         log::info!("Final set of target names: {:?}", target_names);
         let delete_nodes = self.flatten_unreferenced(target_func, &target_names);
+
+        let bindings = self.constant_bindings(target_func);
+        let delete_nodes = self.prune_dead_branches(target_func, delete_nodes, &bindings);
+
         let delete_ranges = self.coalesce_ranges(&delete_nodes);
 
         Ok(delete_ranges)
     }
 
-    fn get_capture<'a>(&self, query: &tree_sitter::Query, capture_name: &str, node: tree_sitter::Node<'a>, content: &[u8]) -> Vec<tree_sitter::Node<'a>> {
-        let capture_idx = query.capture_index_for_name(capture_name).unwrap();
-        let mut cursor = tree_sitter::QueryCursor::new();
+    /// Constants known within `scope`: for every `propagating_query` dest/source pair whose
+    /// source folds to a `Value` (see `constant_eval::eval`), the dest's own text mapped to that
+    /// value, so a later plain-identifier use of it folds too even though the literal itself is
+    /// further up the scope.
+    fn constant_bindings(&self, scope: tree_sitter::Node) -> HashMap<String, Value> {
+        let mut bindings = HashMap::new();
+
+        for [dest, source] in self.get_captures(&self.config.propagating_query, ["dest", "source"], scope, self.src.as_bytes()) {
+            let name = self.src[dest.byte_range()].to_string();
+
+            match constant_eval::eval(&self.config, source, self.src.as_bytes(), &bindings) {
+                Some(value) => { bindings.insert(name, value); },
+                // Reassigned to something non-constant - it's no longer safe to assume the
+                // earlier binding still holds for uses after this point.
+                None => { bindings.remove(&name); },
+            }
+        }
 
-        cursor
-        .captures(query, node, content)
-        .map(|(m, _)| m.captures.iter().filter(|c| c.index == capture_idx).map(|c| c.node)).into_iter().flatten().collect()
+        bindings
+    }
+
+    /// Drop branches whose `if`/`while` condition folds to a statically known boolean: an
+    /// always-false `if` with no `else` (or an always-false `while`) is dropped whole, an
+    /// always-false `if`/`else` has its dead consequence dropped, and an always-true `if`/`else`
+    /// has its dead alternative dropped.
+    fn prune_dead_branches<'a>(&self, target_func: tree_sitter::Node<'a>, mut delete_nodes: Vec<tree_sitter::Node<'a>>, bindings: &HashMap<String, Value>) -> Vec<tree_sitter::Node<'a>> {
+        let content = self.src.as_bytes();
+
+        depth_first(target_func).traverse(|node| {
+            // Already dropping an ancestor of this node; nothing more to prune underneath it.
+            if delete_nodes.iter().any(|d| d.start_byte() <= node.start_byte() && d.end_byte() >= node.end_byte()) {
+                return false;
+            }
+
+            let condition = match node.kind() {
+                "if_statement" | "while_statement" => node.child_by_field_name("condition"),
+                _ => None,
+            };
+
+            let known = match condition.and_then(|c| constant_eval::eval(&self.config, c, content, bindings)).and_then(|v| v.as_bool()) {
+                Some(known) => known,
+                None => return true,
+            };
+
+            match (node.kind(), known, node.child_by_field_name("alternative")) {
+                ("while_statement", false, _) => delete_nodes.push(node),
+                ("if_statement", true, Some(alternative)) => delete_nodes.push(alternative),
+                ("if_statement", false, Some(_)) => {
+                    if let Some(consequence) = node.child_by_field_name("consequence") {
+                        delete_nodes.push(consequence);
+                    }
+                },
+                ("if_statement", false, None) => delete_nodes.push(node),
+                _ => return true,
+            }
+
+            false
+        });
+
+        delete_nodes.sort_by_key(|n| n.start_byte());
+        delete_nodes
+    }
+
+    fn get_capture<'a>(&self, query: &tree_sitter::Query, capture_name: &str, node: tree_sitter::Node<'a>, content: &[u8]) -> Vec<tree_sitter::Node<'a>> {
+        crate::slicer_config::query_capture(query, capture_name, node, content)
     }
 
     fn get_captures<'a, const COUNT: usize>(&self, query: &tree_sitter::Query, capture_names: [&str; COUNT], node: tree_sitter::Node<'a>, content: &[u8]) -> Vec<[tree_sitter::Node<'a>; COUNT]> {
-        let capture_idxs: Vec<u32> = capture_names.iter().map(|name| query.capture_index_for_name(name).unwrap()).collect();
-        let mut cursor = tree_sitter::QueryCursor::new();
-
-        cursor
-        .matches(query, node, content)
-        .map(|m| {
-            let capture_map: HashMap<u32, tree_sitter::Node> = m.captures.iter().map(|c| (c.index, c.node)).collect();
-            capture_idxs.iter().map(|idx| capture_map[idx]).collect::<Vec<tree_sitter::Node>>().try_into().unwrap()
-        }).collect()
+        crate::slicer_config::query_captures(query, capture_names, node, content)
     }
 
     fn rewrite_names(&self, node: &tree_sitter::Node, rename_map: &HashMap<NameRef, String>, src: &str) -> String {
@@ -429,7 +523,265 @@ impl Slicer {
         rewritten_src
     }
 
+    /// Find the identifier token at `point`, if any - e.g. to resolve the callee name at a
+    /// call-site for call-site -> definition lookups.
+    pub fn identifier_at_point(&mut self, point: tree_sitter::Point) -> Result<Option<String>, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+        let root_node = tree.root_node();
+
+        Ok(self.node_of_kind_for_point(&root_node, &self.config.identifier_types, point)
+            .map(|n| self.src[n.byte_range()].to_string()))
+    }
+
+    /// Find the name of a definition node for outline purposes: the first identifier-type
+    /// descendant of its `declarator`/`name` field if it has one (since searching the whole node
+    /// would also walk into the body and pick up unrelated identifiers), falling back to searching
+    /// the whole node for languages without such a field.
+    fn outline_name(&self, node: &tree_sitter::Node) -> String {
+        let search_root = node.child_by_field_name("declarator")
+            .or_else(|| node.child_by_field_name("name"))
+            .unwrap_or(*node);
+
+        depth_first(search_root)
+            .find(|descendant| self.config.identifier_types.contains(&descendant.kind()))
+            .map(|n| self.src[n.byte_range()].to_string())
+            .unwrap_or_else(|| "<anonymous>".to_string())
+    }
+
+    /// Build a nested tree of the definitions in the file (per `config.outline_types`), e.g. for
+    /// use as a file outline/symbol list.
+    ///
+    /// This is built on top of `traverse_with_depth`: `on_descent`/`on_ascent` track the current
+    /// tree depth, and a `Vec` of "children found so far" is kept per depth. A definition found at
+    /// depth `d` collects everything found between the DESCEND into its first child and the
+    /// ASCEND back out of it - i.e. everything at depth `d`'s children level - as its `children`.
+    pub fn outline(&mut self) -> Result<Vec<Symbol>, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+
+        let mut levels: Vec<Vec<Symbol>> = vec![vec![]];
+        let mut open: HashMap<usize, (String, &'static str, tree_sitter::Range)> = HashMap::new();
+        let mut depth = 0usize;
+
+        depth_first(tree.root_node()).traverse_with_depth(
+            |node| {
+                if let Some(&(_, kind)) = self.config.outline_types.iter().find(|&&(node_kind, _)| node_kind == node.kind()) {
+                    open.insert(depth, (self.outline_name(&node), kind, node.range()));
+                }
+                true
+            },
+            |_, _| {
+                depth += 1;
+                levels.push(vec![]);
+            },
+            |_, _| {
+                let children = levels.pop().unwrap();
+                depth -= 1;
+                match open.remove(&depth) {
+                    Some((name, kind, range)) => levels[depth].push(Symbol{name, kind, range, children}),
+                    None => levels[depth].extend(children),
+                }
+            },
+        );
+
+        Ok(levels.pop().unwrap())
+    }
+
+    /// Extract the statements spanning `range` into a newly synthesized function, replacing them
+    /// at the call site with a call to it. This is the inverse of `inline`.
+    ///
+    /// Parameters are inferred as the free variables read within the span whose definition lives
+    /// before it (a backward scan for a `propagating_query` assignment/declaration to that name
+    /// preceding the span); the out-param/return value is a variable written within the span that
+    /// is still referenced afterwards. Only a single out-param is supported - if the span writes to
+    /// more than one variable that's still live afterwards, `MultipleExtractOutputsError` is
+    /// returned rather than silently dropping all but one of them, since real multi-value returns
+    /// need out-params or a struct return, which C (the only language wired up today) doesn't have
+    /// a single idiomatic answer for.
+    pub fn extract(&mut self, range: tree_sitter::Range) -> Result<String, SliceError> {
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(lang_err) = parser.set_language(self.config.language) {
+            return Err(SliceError::TreeSitterVersionError(lang_err));
+        }
+
+        let tree = parser.parse(&self.src, None).unwrap();
+        let root_node = tree.root_node();
+
+        let enclosing = self.node_of_kind_for_point(&root_node, &self.config.slice_scope_types, range.start_point)
+            .ok_or(SliceError::NoNameAtPointError(range.start_point))?;
+
+        // Gather the top-level statements within `enclosing` that the span covers.
+        let mut span_nodes = vec![];
+        depth_first(enclosing).traverse(|n| {
+            if n.start_byte() >= range.start_byte && n.end_byte() <= range.end_byte && self.node_matches_query(&self.config.statement_query, "statement", n, self.src.as_bytes()) {
+                span_nodes.push(n);
+                return false;
+            }
+            n.start_byte() < range.end_byte && n.end_byte() > range.start_byte
+        });
+
+        if span_nodes.is_empty() {
+            return Err(SliceError::NoNameAtPointError(range.start_point));
+        }
+
+        let span_start = span_nodes.iter().map(|n| n.start_byte()).min().unwrap();
+        let span_end = span_nodes.iter().map(|n| n.end_byte()).max().unwrap();
+
+        // Free identifiers read anywhere in the span.
+        let mut read_names: Vec<String> = vec![];
+        for span_node in &span_nodes {
+            depth_first(*span_node).traverse(|d| {
+                if self.config.identifier_types.contains(&d.kind()) {
+                    let name = self.src[d.byte_range()].to_string();
+                    if !read_names.contains(&name) {
+                        read_names.push(name);
+                    }
+                    return false;
+                }
+                true
+            });
+        }
+
+        // Every dest/source pair `propagating_query` finds anywhere in `enclosing`, used below for
+        // both the within-span writes and the before-span definitions.
+        let propagating_pairs = self.get_captures(&self.config.propagating_query, ["dest", "source"], enclosing, self.src.as_bytes());
+
+        // Names assigned to within the span, via `propagating_query`.
+        let mut written_names: Vec<String> = vec![];
+        for [defs_node, _] in &propagating_pairs {
+            if defs_node.start_byte() >= span_start && defs_node.end_byte() <= span_end {
+                let name = self.src[defs_node.byte_range()].to_string();
+                if !written_names.contains(&name) {
+                    written_names.push(name);
+                }
+            }
+        }
+
+        let is_defined_before = |name: &str| {
+            propagating_pairs.iter().any(|[defs_node, _]| {
+                defs_node.end_byte() <= span_start && &self.src[defs_node.byte_range()] == name
+            })
+        };
+
+        let is_used_after = |name: &str| {
+            let mut found = false;
+            depth_first(enclosing).traverse(|n| {
+                if n.start_byte() >= span_end && self.config.identifier_types.contains(&n.kind()) && &self.src[n.byte_range()] == name {
+                    found = true;
+                }
+                true
+            });
+            found
+        };
+
+        let params: Vec<&String> = read_names.iter().filter(|name| !written_names.contains(name) && is_defined_before(name)).collect();
+        let out_vars: Vec<&String> = written_names.iter().filter(|name| is_used_after(name)).collect();
+        if out_vars.len() > 1 {
+            return Err(SliceError::MultipleExtractOutputsError(out_vars.into_iter().cloned().collect()));
+        }
+        let out_var = out_vars.into_iter().next();
+
+        let function_name = "extracted_function";
+        let call_args = params.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
+
+        // TODO: reindent the body to the new function's nesting depth rather than reusing the
+        // original statements' indentation verbatim.
+        let body: String = span_nodes.iter().map(|n| &self.src[n.byte_range()]).collect::<Vec<_>>().join("\n    ");
+
+        let new_function = match out_var {
+            Some(out_var) => format!(
+                "int {}({}) {{\n    {}\n    return {};\n}}\n\n",
+                function_name, call_args, body, out_var,
+            ),
+            None => format!(
+                "void {}({}) {{\n    {}\n}}\n\n",
+                function_name, call_args, body,
+            ),
+        };
+
+        let call_site = match out_var {
+            Some(out_var) => format!("{} = {}({});", out_var, function_name, call_args),
+            None => format!("{}({});", function_name, call_args),
+        };
+
+        let mut new_src = self.src[..enclosing.start_byte()].to_string();
+        new_src += &new_function;
+        new_src += &self.src[enclosing.start_byte()..span_start];
+        new_src += &call_site;
+        new_src += &self.src[span_end..];
+
+        Ok(new_src)
+    }
+
     pub fn inline(&mut self, point: tree_sitter::Point, target_content: &str, target_point: tree_sitter::Point) -> Result<String, SliceError> {
+        let (range, replacement) = self.build_inline_replacement(point, target_content, target_point)?;
+        Ok(format!("{}{}{}", &self.src[..range.start], replacement, &self.src[range.end..]))
+    }
+
+    /// Same transform as `inline`, but returned as a single minimal `edit::TextEdit` over the
+    /// replaced span instead of a whole rewritten buffer - the only thing that changes is the
+    /// callsite's line through the callsite's own end byte.
+    pub fn inline_edit(&mut self, point: tree_sitter::Point, target_content: &str, target_point: tree_sitter::Point) -> Result<edit::TextEdit, SliceError> {
+        let (range, new_text) = self.build_inline_replacement(point, target_content, target_point)?;
+        let index = edit::LineIndex::new(&self.src);
+
+        Ok(edit::TextEdit{
+            start: index.offset_to_position(&self.src, range.start),
+            end: index.offset_to_position(&self.src, range.end),
+            new_text,
+        })
+    }
+
+    /// Inline several independent callsites, splicing them into `self.src` in one pass instead of
+    /// once per site. Each entry in `sites` is the same `(point, target_content,
+    /// target_point)` triple as `inline`'s arguments. Every site's replacement is computed against
+    /// the original `self.src` via `build_inline_replacement`, which parses `self.src` and that
+    /// site's `target_content` anew for each site - none of them observe each other's
+    /// edits - so sites must not overlap; if two do, `OverlappingInlineSitesError` is returned
+    /// rather than silently picking one. The replacements are then sorted by `start_byte` and
+    /// assembled into the final buffer in a single left-to-right pass, copying the untouched
+    /// bytes between consecutive sites rather than repeatedly re-slicing a shrinking/growing
+    /// buffer.
+    pub fn inline_batch(&mut self, sites: &[(tree_sitter::Point, &str, tree_sitter::Point)]) -> Result<String, SliceError> {
+        let mut replacements: Vec<(std::ops::Range<usize>, String)> = sites.iter()
+            .map(|(point, target_content, target_point)| self.build_inline_replacement(*point, target_content, *target_point))
+            .collect::<Result<_, _>>()?;
+
+        replacements.sort_by_key(|(range, _)| range.start);
+
+        for pair in replacements.windows(2) {
+            let (prev, cur) = (&pair[0].0, &pair[1].0);
+            if cur.start < prev.end {
+                return Err(SliceError::OverlappingInlineSitesError(prev.start, prev.end, cur.start, cur.end));
+            }
+        }
+
+        let mut new_src = String::new();
+        let mut prev_end = 0;
+        for (range, replacement) in &replacements {
+            new_src += &self.src[prev_end..range.start];
+            new_src += replacement;
+            prev_end = range.end;
+        }
+        new_src += &self.src[prev_end..];
+
+        Ok(new_src)
+    }
+
+    /// Compute the inlining rewrite: the byte range (from the start of the callsite's own line
+    /// through the end of the callsite) to replace, and the replacement text (hoisted temps, the
+    /// reindented function body, and the rewritten callsite expression). `inline` and
+    /// `inline_edit` differ only in how they splice this back into (or alongside) `self.src`.
+    fn build_inline_replacement(&mut self, point: tree_sitter::Point, target_content: &str, target_point: tree_sitter::Point) -> Result<(std::ops::Range<usize>, String), SliceError> {
         let mut parser = tree_sitter::Parser::new();
         if let Err(lang_err) = parser.set_language(self.config.language) {
             return Err(SliceError::TreeSitterVersionError(lang_err));
@@ -469,22 +821,29 @@ impl Slicer {
         // e.g. inlining `foo(x=bar(baz))` would result in `let x = bar(baz); {contents of foo}`
         // to avoid giving the impression that `bar(baz)` is evaluated twice
         let mut temps: Vec<InlineTempVar> = vec![];
-        
+
         let mut rename_map: HashMap<NameRef, String> = HashMap::new();
 
+        // Seeds type_infer's fallback path below for languages whose function_query can't
+        // capture a concrete @param_type (see SlicerConfig::type_default).
+        let type_bindings = type_infer::bindings(&self.config, root_node, self.src.as_bytes());
+
         for (arg, [param_name_node, param_type_node]) in call_args.iter().zip(function_params.iter()) {
             let param_name = self.name_at_point(&function_definition_file_root_node, param_name_node.start_position()).ok_or(SliceError::NoNameAtPointError(param_name_node.start_position()))?;
 
-            if self.config.constant_types.contains(&arg.kind()) || self.config.name_types.contains(&arg.kind()) {
+            if self.node_matches_query(&self.config.constant_query, "constant", *arg, self.src.as_bytes()) || self.config.name_types.contains(&arg.kind()) {
                 rename_map.insert(param_name, self.src[arg.byte_range()].to_string());
             } else {
                 let inline_name = format!("inline_{}", &target_content[param_name.node.byte_range()]);
+                let typ = if param_type_node.byte_range().is_empty() {
+                    type_infer::infer(&self.config, *arg, root_node, self.src.as_bytes(), &type_bindings).unwrap_or(self.config.type_default).to_string()
+                } else {
+                    target_content[param_type_node.byte_range()].to_string()
+                };
                 temps.push(InlineTempVar{
                     name: inline_name.clone(),
                     value: self.src[arg.byte_range()].to_string(),
-                    // TODO: check if type is set before trying to pull content
-                    // wont be applicable in e.g. python
-                    typ: target_content[param_type_node.byte_range()].to_string(),
+                    typ,
                 });
                 rename_map.insert(param_name, inline_name);
             }
@@ -518,12 +877,16 @@ impl Slicer {
         let src_lines: Vec<&str> = self.src.split("\n").collect();
         let callsite_whitespace: String = src_lines[callsite.start_position().row].chars().take_while(|c| c.is_whitespace()).collect();
 
-        let mut new_src = src_lines[0..callsite.start_position().row].join("\n") + "\n";
+        // The byte range being replaced starts at the beginning of the callsite's own line, since
+        // any hoisted temps are inserted as whole lines before it.
+        let line_start_byte = callsite.start_byte() - callsite.start_position().column;
+
+        let mut replacement = String::new();
 
         for temp in temps {
-            new_src += &callsite_whitespace;
-            new_src += &temp.format(self.config.temp_var_format);
-            new_src += "\n";
+            replacement += &callsite_whitespace;
+            replacement += &temp.format(self.config.temp_var_format);
+            replacement += "\n";
         }
 
         let mut start_byte = 0;
@@ -575,61 +938,92 @@ impl Slicer {
         inline_src += &target_content[prev_byte..end_byte];
 
         for line in inline_src.split("\n") {
-            new_src += &callsite_whitespace;
-            new_src += &(line.strip_prefix(&definition_whitespace).unwrap_or(&line));
-            new_src += "\n";
+            replacement += &callsite_whitespace;
+            replacement += &(line.strip_prefix(&definition_whitespace).unwrap_or(&line));
+            replacement += "\n";
         }
 
-        new_src = new_src[..new_src.len()-1].to_string();
+        replacement = replacement[..replacement.len()-1].to_string();
 
-        new_src += &src_lines[callsite.start_position().row][0..callsite.start_position().column];
+        replacement += &src_lines[callsite.start_position().row][0..callsite.start_position().column];
         match callsite_rewrite {
-            RewriteValue::String(s) => new_src += &s,
-            RewriteValue::Node(n) => new_src += &self.rewrite_names(&n, &rename_map, &target_content),
+            RewriteValue::String(s) => replacement += &s,
+            RewriteValue::Node(n) => replacement += &self.rewrite_names(&n, &rename_map, &target_content),
             RewriteValue::None => (),
         }
 
-        new_src += &self.src[callsite.end_byte()..];
+        Ok((line_start_byte..callsite.end_byte(), replacement))
+    }
+}
 
-        Ok(new_src)
+/// Flatten `ranges` - which may be unsorted, overlapping, or nested - into a sorted set of
+/// disjoint byte spans to delete: sort by `start_byte`, then walk them maintaining a "current"
+/// merged span, extending its end to `max(end, next.end)` whenever the next range starts at or
+/// before it instead of emitting a second cut. This also handles full containment (a range nested
+/// inside another just extends the current span to no more than its own end, which it was already
+/// at), so the caller never has to pre-sort or de-overlap its input.
+fn merge_ranges(ranges: &Vec<tree_sitter::Range>) -> Vec<tree_sitter::Range> {
+    let mut sorted: Vec<&tree_sitter::Range> = ranges.iter().collect();
+    sorted.sort_by_key(|range| range.start_byte);
+
+    let mut merged: Vec<tree_sitter::Range> = vec![];
+    for range in sorted {
+        match merged.last_mut() {
+            Some(prev) if range.start_byte <= prev.end_byte => {
+                if range.end_byte > prev.end_byte {
+                    prev.end_byte = range.end_byte;
+                    prev.end_point = range.end_point;
+                }
+            }
+            _ => merged.push(range.clone()),
+        }
     }
+
+    merged
 }
 
-/// Delete the given ranges from the src, returning both the source with lines removed as well as
-/// the target_point adjusted to be pointing to the same location.
-/// Ranges is assumed to be pre-sorted.
+/// Delete the given ranges from the src, returning both the resulting source and `target_point`
+/// adjusted to still point at the same location. `ranges` may be unsorted, overlapping, or
+/// nested - they're normalized via `merge_ranges` first. Works entirely in byte offsets (as
+/// opposed to splicing `row`/`column` line slices, which can't express deleting the middle of a
+/// line without corrupting whatever's left on it).
 pub fn delete_ranges(src: &str, ranges: &Vec<tree_sitter::Range>, target_point: tree_sitter::Point) -> (String, tree_sitter::Point) {
-    let src_lines: Vec<&str> = src.split("\n").collect();
+    let merged = merge_ranges(ranges);
+    let index = edit::LineIndex::new(src);
+    let target_byte = index.point_to_offset(target_point);
 
-    let mut target_point = target_point.clone();
-    let mut new: Vec<&str> = vec![];
+    let mut new_src = String::with_capacity(src.len());
+    let mut prev_end = 0;
+    let mut deleted_before_target = 0;
 
-    let mut i = 0;
-    for range in ranges {
-        if i < range.start_point.row {
-            new.extend(src_lines[i..range.start_point.row].iter());
-        }
-        let prefix = &src_lines[range.start_point.row][0..range.start_point.column];
-        if !prefix.trim().is_empty() {
-            new.push(prefix);
-        }
-        let suffix = &src_lines[range.end_point.row][range.end_point.column..];
-        if !suffix.trim().is_empty() {
-            new.push(suffix);
-        }
-        i = range.end_point.row + 1;
-
-        // the target point must be included in the final slice response (i.e. not deleted)
-        // so no need to check for any weird cases.
-        if range.end_point.row < target_point.row {
-            let mut deleted_lines = range.end_point.row - range.start_point.row;
-            if prefix.trim().is_empty() || suffix.trim().is_empty() {
-                deleted_lines += 1;
-            }
-            target_point.row -= deleted_lines;
+    for range in &merged {
+        new_src += &src[prev_end..range.start_byte];
+
+        // the target point must be included in the final response (i.e. not itself deleted), so
+        // no need to handle the target point falling inside a deleted range.
+        if range.end_byte <= target_byte {
+            deleted_before_target += range.end_byte - range.start_byte;
         }
+
+        prev_end = range.end_byte;
     }
-    new.extend(src_lines[i..].iter());
+    new_src += &src[prev_end..];
+
+    let new_target_point = edit::LineIndex::new(&new_src).offset_to_point(target_byte - deleted_before_target);
+
+    (new_src, new_target_point)
+}
 
-    (new.join("\n"), target_point)
+/// Same deletions as `delete_ranges`, but as minimal `edit::TextEdit`s (one per merged span)
+/// instead of a whole rewritten buffer. Like `delete_ranges`, `ranges` may be unsorted,
+/// overlapping, or nested.
+pub fn delete_range_edits(src: &str, ranges: &Vec<tree_sitter::Range>) -> Vec<edit::TextEdit> {
+    let merged = merge_ranges(ranges);
+    let index = edit::LineIndex::new(src);
+
+    merged.iter().map(|range| edit::TextEdit{
+        start: index.offset_to_position(src, range.start_byte),
+        end: index.offset_to_position(src, range.end_byte),
+        new_text: String::new(),
+    }).collect()
 }
\ No newline at end of file
@@ -32,28 +32,41 @@ pub struct SlicerConfig {
     /// Type names representing any possible "complete" name (e.g. `self.foo.bar`)
     pub name_types: Vec<&'static str>,
 
-    /// Type names representing constants (constant integers, true/false, null, etc.)
-    pub constant_types: Vec<&'static str>,
+    /// The tree-sitter query used to recognize constants (constant integers, true/false, null,
+    /// etc.). Matching nodes should be captured as @constant.
+    pub constant_query: tree_sitter::Query,
 
-    /// Type names and the field names for the descendant destination and source representing ways a
-    /// variable can flow into a new variable (e.g. assignment).
-    /// e.g. ("assignment_expression", ("left", "right"))
-    pub propagating_types: Vec<(&'static str, (&'static str, &'static str))>,
+    /// The tree-sitter query used to find ways a variable can flow into a new variable (e.g.
+    /// assignment, destructuring). Should capture the assigned-to side as @dest and the
+    /// assigned-from side as @source; a single pattern can capture more than one dest/source pair
+    /// per match for constructs like multiple-assignment.
+    pub propagating_query: tree_sitter::Query,
 
-    /// Type names representing statements. Can use "inheritance" information from node-types.
-    pub statement_types: Vec<&'static str>,
+    /// The tree-sitter query used to recognize statements. Matching nodes should be captured as
+    /// @statement. Can lean on supertypes (e.g. `(_statement)`) to cover a whole family of node
+    /// kinds at once.
+    pub statement_query: tree_sitter::Query,
 
     /// Type names representing scopes in which we can slice (just functions?)
     pub slice_scope_types: Vec<&'static str>,
 
+    /// Type names representing a definition that should show up in the file outline (functions,
+    /// methods, classes/structs, ...), paired with the symbol kind label to report for it (e.g.
+    /// "function", "class").
+    pub outline_types: Vec<(&'static str, &'static str)>,
+
     /// Type names representing variable accessibility "boundaries" in the language, where
     /// variables defined within are not accessible outside of.
     /// For Python, this would be function level, but for C-like languages, this would be
     /// block-level.
     pub var_definition_scope_types: Vec<&'static str>,
 
-    // In general, the "accuracy" with detecting names and constructs is lower for slicing than it
-    // is for inlining, hence the change to using actual queries below for inlining related things.
+    // Slicing used to rely on flat type-name lists (`propagating_types`, `statement_types`,
+    // `constant_types`) where inlining already used tree-sitter queries, and was less accurate for
+    // it - a flat list can't express "this assignment form defines two names" the way a query
+    // pattern with repeated captures can. The three queries above and the inlining queries below
+    // all go through the same query machinery; `function_call_types` just below is still a flat
+    // type-name list.
     // https://tree-sitter.github.io/tree-sitter/using-parsers#query-syntax
 
     /// Type names representing function calls.
@@ -76,6 +89,12 @@ pub struct SlicerConfig {
     /// The format string used to generate temporary variables.
     /// e.g. `{type} {name} = {value};`
     pub temp_var_format: &'static str,
+
+    /// Fallback for `{type}` in `temp_var_format` when `type_infer` can't pin down a concrete
+    /// type for a hoisted temp (e.g. `"auto"` for C++, `"var"` for JS, `""` for languages that
+    /// don't declare a temp's type at all). Unused by C, which always has an explicit
+    /// `@param_type`.
+    pub type_default: &'static str,
 }
 
 #[derive(Deserialize)]
@@ -85,7 +104,52 @@ struct NodeType {
     subtypes: Vec<NodeType>,
 }
 
-fn expand_node_types(node_types_json: &str) -> HashMap<String, Vec<String>> {
+/// Whether `node` itself is captured as `capture_name` by `query`, i.e. `node` is a match for one
+/// of the query's alternatives (not just some node within `node`'s subtree). Shared by `Slicer`
+/// and `constant_eval`, neither of which otherwise needs the other's state to ask this.
+pub(crate) fn node_matches_query(query: &tree_sitter::Query, capture_name: &str, node: tree_sitter::Node, content: &[u8]) -> bool {
+    let capture_idx = query.capture_index_for_name(capture_name).unwrap();
+    let mut cursor = tree_sitter::QueryCursor::new();
+
+    cursor
+    .matches(query, node, content)
+    .any(|m| m.captures.iter().any(|c| c.index == capture_idx && c.node == node))
+}
+
+/// Every node captured as `capture_name` anywhere within `node`'s subtree (including `node`
+/// itself). Shared by `Slicer` and `resolver`, neither of which otherwise needs the other's state
+/// to ask this.
+pub(crate) fn query_capture<'a>(query: &tree_sitter::Query, capture_name: &str, node: tree_sitter::Node<'a>, content: &[u8]) -> Vec<tree_sitter::Node<'a>> {
+    let capture_idx = query.capture_index_for_name(capture_name).unwrap();
+    let mut cursor = tree_sitter::QueryCursor::new();
+
+    cursor
+    .captures(query, node, content)
+    .map(|(m, _)| m.captures.iter().filter(|c| c.index == capture_idx).map(|c| c.node)).into_iter().flatten().collect()
+}
+
+/// Every match of `query` within `node`'s subtree, as the tuple of nodes captured under
+/// `capture_names` (in that order). Shared by `Slicer` and `resolver`.
+pub(crate) fn query_captures<'a, const COUNT: usize>(query: &tree_sitter::Query, capture_names: [&str; COUNT], node: tree_sitter::Node<'a>, content: &[u8]) -> Vec<[tree_sitter::Node<'a>; COUNT]> {
+    let capture_idxs: Vec<u32> = capture_names.iter().map(|name| query.capture_index_for_name(name).unwrap()).collect();
+    let mut cursor = tree_sitter::QueryCursor::new();
+
+    cursor
+    .matches(query, node, content)
+    .map(|m| {
+        let capture_map: HashMap<u32, tree_sitter::Node> = m.captures.iter().map(|c| (c.index, c.node)).collect();
+        capture_idxs.iter().map(|idx| capture_map[idx]).collect::<Vec<tree_sitter::Node>>().try_into().unwrap()
+    }).collect()
+}
+
+/// The first `identifier_types` descendant of `node` - its "base" identifier, e.g. `self` in
+/// `self.foo.bar`, or the declarator name under a pointer/array declarator. Shared by `Slicer`,
+/// `resolver`, and `type_infer`.
+pub(crate) fn base_identifier<'a>(identifier_types: &[&'static str], node: tree_sitter::Node<'a>) -> Option<tree_sitter::Node<'a>> {
+    crate::traverse::depth_first(node).find(|descendant| identifier_types.contains(&descendant.kind()))
+}
+
+pub(crate) fn expand_node_types(node_types_json: &str) -> HashMap<String, Vec<String>> {
     let mut subtypes = HashMap::new();
 
     for node_type in serde_json::from_str::<Vec<NodeType>>(node_types_json).unwrap() {
@@ -97,247 +161,44 @@ fn expand_node_types(node_types_json: &str) -> HashMap<String, Vec<String>> {
     subtypes
 }
 
+/// Compatibility shim for configs still specifying `propagating_query` as the old
+/// `(kind, (dest_field, source_field))` tuple list: synthesizes the equivalent query, alternating
+/// over every kind, at construction time so the slicing engine never has to know which form a
+/// given config was authored in.
+pub(crate) fn build_propagating_query(language: tree_sitter::Language, types: &[(String, (String, String))]) -> Result<tree_sitter::Query, tree_sitter::QueryError> {
+    let patterns: Vec<String> = types.iter()
+        .map(|(kind, (dest_field, source_field))| format!("({} {}: (_) @dest {}: (_) @source)", kind, dest_field, source_field))
+        .collect();
+
+    tree_sitter::Query::new(language, &format!("[{}]", patterns.join("\n")))
+}
+
+/// Compatibility shim for configs still specifying `statement_query` as the old flat
+/// `statement_types` list: synthesizes the equivalent query, alternating over every kind.
+pub(crate) fn build_statement_query(language: tree_sitter::Language, types: &[String]) -> Result<tree_sitter::Query, tree_sitter::QueryError> {
+    let patterns: Vec<String> = types.iter().map(|kind| format!("({}) @statement", kind)).collect();
+    tree_sitter::Query::new(language, &format!("[{}]", patterns.join("\n")))
+}
+
+/// Compatibility shim for configs still specifying `constant_query` as the old flat
+/// `constant_types` list: synthesizes the equivalent query, alternating over every kind.
+pub(crate) fn build_constant_query(language: tree_sitter::Language, types: &[String]) -> Result<tree_sitter::Query, tree_sitter::QueryError> {
+    let patterns: Vec<String> = types.iter().map(|kind| format!("({}) @constant", kind)).collect();
+    tree_sitter::Query::new(language, &format!("[{}]", patterns.join("\n")))
+}
+
 pub fn from_guessed_language(language: guess_language::Language) -> Option<SlicerConfig> {
     use guess_language::Language::*;
 
-    match language {
-        C => {
-            // https://github.com/tree-sitter/tree-sitter-c/blob/master/src/grammar.json
-            Some(SlicerConfig{
-                language: unsafe {tree_sitter_c()},
-                subtypes: expand_node_types(include_str!("../vendor/tree-sitter-c/src/node-types.json")),
-                identifier_types: vec!["identifier", "field_identifier"],
-                name_types: vec!["identifier", "field_expression"],
-                constant_types: vec!["null", "true", "false", "number_literal", "string_literal", "character_literal"],
-                propagating_types: vec![
-                    ("assignment_expression", ("left", "right")),
-                    ("init_declarator", ("declarator", "value")),
-                ],
-                statement_types: vec!["_statement", "declaration"],
-                slice_scope_types: vec!["function_definition"],
-                var_definition_scope_types: vec!["compound_statement"],
-                function_call_types: vec!["call_expression"],
-                function_query: tree_sitter::Query::new(unsafe {tree_sitter_c()}, "
-                    (function_definition
-                        type: (_type_specifier) @function_type
-                        declarator: (function_declarator
-                            parameters: (parameter_list
-                                (parameter_declaration
-                                    type: (_type_specifier) @param_type
-                                    declarator: (_declarator) @param_name
-                                )
-                            )
-                        )
-                        body: (compound_statement) @function_body
-                    )").unwrap(),
-                call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_c()}, "
-                    (call_expression
-                        arguments: (argument_list
-                            \"(\"
-                            (_expression) @value
-                            \")\"
-                        )
-                    )").unwrap(),
-                returns_query: tree_sitter::Query::new(unsafe {tree_sitter_c()}, "
-                    (return_statement
-                        (_expression) @return_value
-                    ) @return_statement").unwrap(),
-                temp_var_format: "{type} {name} = {value};",
-            })
-        }
-        // CPlusPlus => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_cpp()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-cpp/src/node-types.json")),
-        //         identifier_types: vec!["identifier", "field_identifier"],
-        //         name_types: vec!["identifier", "field_expression"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment_expression", ("left", "right")),
-        //             ("init_declarator", ("declarator", "value")),
-        //             // TODO: for in
-        //         ],
-        //         statement_types: vec!["_statement", "declaration"],
-        //         slice_scope_types: vec!["function_definition"],
-        //         var_definition_scope_types: vec!["compound_statement"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_cpp()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_cpp()}, "").unwrap(),
-        //     })
-        // }
-        // CSharp => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_c_sharp()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-c-sharp/src/node-types.json")),
-        //         identifier_types: vec!["identifier"],
-        //         name_types: vec!["identifier", "member_access_expression"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment_expression", ("left", "right")),
-        //             // TODO: for in
-        //         ],
-        //         statement_types: vec!["_statement"],
-        //         slice_scope_types: vec!["_function_body", "method_declaration"],
-        //         var_definition_scope_types: vec!["block"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_c_sharp()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_c_sharp()}, "").unwrap(),
-        //     })
-        // }
-        // Go => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_go()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-go/src/node-types.json")),
-        //         identifier_types: vec!["identifier", "field_identifier"],
-        //         name_types: vec!["identifier", "selector_expression"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment_statement", ("left", "right")),
-        //             ("short_var_declaration", ("left", "right")),
-        //         ],
-        //         statement_types: vec!["_statement"],
-        //         slice_scope_types: vec!["function_declaration"],
-        //         var_definition_scope_types: vec!["block"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_go()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_go()}, "").unwrap(),
-        //     })
-        // }
-        // Java => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_java()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-java/src/node-types.json")),
-        //         identifier_types: vec!["identifier"],
-        //         name_types: vec!["identifier", "field_access"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment_expression", ("left", "right")),
-        //             // TODO: for in
-        //         ],
-        //         statement_types: vec!["statement"],
-        //         slice_scope_types: vec!["method_declaration"],
-        //         var_definition_scope_types: vec!["block"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_java()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_java()}, "").unwrap(),
-        //     })
-        // }
-        // JavaScript => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_javascript()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-javascript/src/node-types.json")),
-        //         identifier_types: vec!["identifier", "property_identifier"],
-        //         name_types: vec!["identifier", "member_expression"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment_expression", ("left", "right")),
-        //             ("variable_declarator", ("name", "value")),
-        //         ],
-        //         statement_types: vec!["statement"],
-        //         slice_scope_types: vec!["function_declaration", "generator_function_declaration", "arrow_function", "method_definition"],
-        //         var_definition_scope_types: vec!["statement_block"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_javascript()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_javascript()}, "").unwrap(),
-        //     })
-        // }
-        // Python => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_python()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-python/src/node-types.json")),
-        //         identifier_types: vec!["identifier"],
-        //         name_types: vec!["identifier", "attribute"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment", ("left", "right")),
-        //             ("with_item", ("alias", "value")),
-        //         ],
-        //         statement_types: vec!["_compound_statement", "_simple_statement"],
-        //         slice_scope_types: vec!["function_definition"],
-        //         var_definition_scope_types: vec!["function_definition"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_python()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_python()}, "").unwrap(),
-        //     })
-        // }
-        // Ruby => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_ruby()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-ruby/src/node-types.json")),
-        //         identifier_types: vec!["identifier"],
-        //         name_types: vec!["identifier", "call"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment", ("left", "right")),
-        //         ],
-        //         // Can't use _primary since that includes like `integer`
-        //         statement_types: vec!["_statement", "begin", "while", "until", "if", "unless", "for", "case"],
-        //         slice_scope_types: vec!["method", "singleton_method"],
-        //         var_definition_scope_types: vec!["method", "singleton_method"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_ruby()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_ruby()}, "").unwrap(),
-        //     })
-        // }
-        // Rust => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_rust()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-rust/src/node-types.json")),
-        //         identifier_types: vec!["identifier"],
-        //         name_types: vec!["identifier", "token_tree"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment_expression", ("left", "right")),
-        //             ("let_declaration", ("pattern", "value")),
-        //             // TODO: for in, if let, while let if those don't already work
-        //         ],
-        //         // # treesitter (and maybe rust's spec?) doesn't have a normal "statement"
-        //         // so we have to do our best and enumerate what is normally used as a statement
-        //         statement_types: vec![
-        //             "let_declaration",
-        //             "macro_invocation",
-        //             "assignment_expression",
-        //             "await_expression",
-        //             "call_expression",
-        //             "compound_assignment_expr",
-        //             "for_expression",
-        //             "if_expression",
-        //             "if_let_expression",
-        //             "loop_expression",
-        //             "match_expression",
-        //             "return_expression",
-        //             "struct_expression",
-        //             "try_expression",
-        //             "while_expression",
-        //             "while_let_expression",
-        //         ],
-        //         slice_scope_types: vec!["function_item"],
-        //         var_definition_scope_types: vec!["block"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_rust()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_rust()}, "").unwrap(),
-        //     })
-        // }
-        // TypeScript => {
-        //     Some(SlicerConfig{
-        //         language: unsafe {tree_sitter_typescript()},
-        //         subtypes: expand_node_types(include_str!("../vendor/tree-sitter-typescript/typescript/src/node-types.json")),
-        //         identifier_types: vec!["identifier", "property_identifier"],
-        //         name_types: vec!["identifier", "member_expression"],
-        //         constant_types: vec![],  // TODO
-        //         propagating_types: vec![
-        //             ("assignment_expression", ("left", "right")),
-        //             ("variable_declarator", ("name", "value")),
-        //         ],
-        //         statement_types: vec!["statement"],
-        //         slice_scope_types: vec!["function_declaration", "generator_function_declaration", "arrow_function", "method_definition"],
-        //         var_definition_scope_types: vec!["statement_block"],
-        //         function_call_types: vec![""],
-        //         function_query: tree_sitter::Query::new(unsafe {tree_sitter_typescript()}, "").unwrap(),
-        //         call_args_query: tree_sitter::Query::new(unsafe {tree_sitter_typescript()}, "").unwrap(),
-        //     })
-        // }
-        _ => None
-    }
+    // The built-in languages live in `config/languages.toml`, loaded through
+    // `config_loader::from_default_config` - the same path a user-supplied TOML/JSON config file
+    // goes through. Only "c" has an entry there today; add a table for a language there (and a
+    // case below) to light it up.
+    let name = match language {
+        C => "c",
+        CPlusPlus | CSharp | Go | Java | JavaScript | Python | Ruby | Rust | TypeScript => return None,
+    };
+
+    crate::config_loader::from_default_config(name).ok()
 }
 
@@ -0,0 +1,108 @@
+//! Lightweight bottom-up type inference, modeled on nac3's `Expr<()> -> Expr<Option<Type>>` fold:
+//! a leaf gets seeded with a type (a function parameter's declared type, a literal's natural
+//! type, a variable's initializer) and a type propagates upward through assignments and calls.
+//! Used to fill in `{type}` in `temp_var_format` for the currently-disabled dynamically-typed
+//! language configs, where `Slicer::inline` otherwise has no declared parameter type to read a
+//! hoisted temp's type from.
+
+use std::collections::HashMap;
+
+use crate::slicer_config::{self, SlicerConfig};
+use crate::traverse::depth_first;
+
+/// An inferred type name, interned so two nodes with the same inferred type compare as equal
+/// `&'static str`s instead of each holding their own allocation.
+pub type Type = &'static str;
+
+fn intern(pool: &mut HashMap<String, Type>, name: &str) -> Type {
+    if let Some(&interned) = pool.get(name) {
+        return interned;
+    }
+    let interned: Type = Box::leak(name.to_string().into_boxed_str());
+    pool.insert(name.to_string(), interned);
+    interned
+}
+
+/// A literal node's natural type, based on its `constant_query` kind - mirrors
+/// `constant_eval::literal_value`'s kind match, but to a type name instead of a value.
+fn literal_type(kind: &str) -> Option<&'static str> {
+    match kind {
+        "true" | "false" => Some("bool"),
+        "number_literal" => Some("int"),
+        "string_literal" | "character_literal" => Some("string"),
+        _ => None,
+    }
+}
+
+/// Find a `slice_scope_types` definition elsewhere in `root` whose name matches `call_node`'s
+/// callee - "a resolved callee when available", per the request: calls to methods, function
+/// pointers, or code outside this file simply won't resolve, and the fold falls back to `None`.
+fn resolve_callee<'a>(config: &SlicerConfig, call_node: tree_sitter::Node<'a>, root: tree_sitter::Node<'a>, content: &[u8]) -> Option<tree_sitter::Node<'a>> {
+    let callee_name = slicer_config::base_identifier(&config.identifier_types, call_node)?.utf8_text(content).ok()?;
+
+    depth_first(root).find(|node| {
+        config.slice_scope_types.contains(&node.kind())
+            && slicer_config::base_identifier(&config.identifier_types, *node).and_then(|n| n.utf8_text(content).ok()) == Some(callee_name)
+    })
+}
+
+fn infer_inner<'a>(config: &SlicerConfig, node: tree_sitter::Node<'a>, root: tree_sitter::Node<'a>, content: &[u8], bindings: &HashMap<String, Type>, pool: &mut HashMap<String, Type>) -> Option<Type> {
+    if config.identifier_types.contains(&node.kind()) {
+        return bindings.get(node.utf8_text(content).ok()?).copied();
+    }
+
+    if slicer_config::node_matches_query(&config.constant_query, "constant", node, content) {
+        return literal_type(node.kind()).map(|name| intern(pool, name));
+    }
+
+    if config.function_call_types.contains(&node.kind()) {
+        let callee = resolve_callee(config, node, root, content)?;
+        return slicer_config::query_captures(&config.returns_query, ["return_statement", "return_value"], callee, content)
+            .into_iter()
+            .find_map(|[_, retval]| infer_inner(config, retval, root, content, bindings, pool));
+    }
+
+    None
+}
+
+/// Fold `node` to a `Type`, given `bindings` for any identifier whose type is already known (see
+/// `bindings` below). Returns `None` when nothing here pins down a type - the caller then falls
+/// back to `SlicerConfig::type_default`.
+pub fn infer<'a>(config: &SlicerConfig, node: tree_sitter::Node<'a>, root: tree_sitter::Node<'a>, content: &[u8], bindings: &HashMap<String, Type>) -> Option<Type> {
+    let mut pool = HashMap::new();
+    infer_inner(config, node, root, content, bindings, &mut pool)
+}
+
+/// Build a name -> `Type` map seeded from `function_query`'s `@param_name`/`@param_type`
+/// captures, then extended by folding each `propagating_query` initializer in source order (the
+/// same single-pass, source-order approach `Slicer::constant_bindings` uses for constant
+/// folding) so a later assignment's inferred type can build on an earlier one, and a reassignment
+/// to an un-inferrable value clears what was known rather than keeping a stale type around.
+pub fn bindings(config: &SlicerConfig, root: tree_sitter::Node, content: &[u8]) -> HashMap<String, Type> {
+    let mut pool = HashMap::new();
+    let mut bindings = HashMap::new();
+
+    for [param_name, param_type] in slicer_config::query_captures(&config.function_query, ["param_name", "param_type"], root, content) {
+        if param_type.byte_range().is_empty() {
+            continue;
+        }
+        if let (Some(name_node), Ok(type_name)) = (slicer_config::base_identifier(&config.identifier_types, param_name), param_type.utf8_text(content)) {
+            if let Ok(name) = name_node.utf8_text(content) {
+                bindings.insert(name.to_string(), intern(&mut pool, type_name));
+            }
+        }
+    }
+
+    for [dest, source] in slicer_config::query_captures(&config.propagating_query, ["dest", "source"], root, content) {
+        if let Some(name_node) = slicer_config::base_identifier(&config.identifier_types, dest) {
+            if let Ok(name) = name_node.utf8_text(content) {
+                match infer_inner(config, source, root, content, &bindings, &mut pool) {
+                    Some(typ) => { bindings.insert(name.to_string(), typ); },
+                    None => { bindings.remove(name); },
+                }
+            }
+        }
+    }
+
+    bindings
+}
@@ -0,0 +1,66 @@
+#![feature(custom_test_frameworks)]
+#![test_runner(datatest::runner)]
+
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::path::Path;
+
+use slicer::fixture::{extract_cursor, extract_tag};
+use slicer::guess_language::guess as guess_language;
+use slicer::slicer::{Slicer, delete_ranges};
+use slicer::slicer_config::from_guessed_language;
+
+/// Fixtures are annotated source followed by a `===` line and the golden output, so a test is
+/// just a before/after source pair instead of a source file plus a hand-computed `TEST:` point.
+fn split_fixture(contents: &str) -> (&str, &str) {
+    contents.split_once("\n===\n").expect("fixture missing '===' golden-output separator")
+}
+
+/// An inline fixture marks the call to inline with `<callsite>...</callsite>` and the target
+/// function's definition with a `$0` cursor; both call and definition live in the same annotated
+/// source, same as `test_inline` in lang_tests.rs passing `input_contents` as its own
+/// `target_content`.
+#[datatest::files("tests/files/", {
+  path in r"fixture_inline.*",
+})]
+fn test_fixture_inline(path: &Path) {
+    let _ = env_logger::try_init();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let (annotated, expected) = split_fixture(&contents);
+
+    let (stripped, callsite) = extract_tag(annotated, "callsite").expect("fixture missing <callsite>");
+    let (src, target_point) = extract_cursor(&stripped, "$0").expect("fixture missing $0 cursor");
+
+    let lang = guess_language(&path, &src).unwrap();
+    let slicer_config = from_guessed_language(lang).unwrap();
+
+    let mut slicer = Slicer{
+        config: slicer_config,
+        src: src.clone(),
+    };
+    let inlined = slicer.inline(callsite.range.start_point, &src, target_point).unwrap();
+
+    // this is "backwards" because pretty_assertions diffs from a to b, and it's more intuitive if
+    // we show what the slicer output is missing.
+    assert_eq!(expected.trim_end(), inlined);
+}
+
+/// A delete-ranges fixture marks the span to delete with `<delete>...</delete>` and the point
+/// whose identity (not byte offset) should survive the deletion with a `$0` cursor.
+#[datatest::files("tests/files/", {
+  path in r"fixture_delete.*",
+})]
+fn test_fixture_delete(path: &Path) {
+    let _ = env_logger::try_init();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let (annotated, expected) = split_fixture(&contents);
+
+    let (stripped, to_delete) = extract_tag(annotated, "delete").expect("fixture missing <delete>");
+    let (src, target_point) = extract_cursor(&stripped, "$0").expect("fixture missing $0 cursor");
+
+    let (deleted, _) = delete_ranges(&src, &vec![to_delete.range], target_point);
+
+    assert_eq!(expected.trim_end(), deleted.trim());
+}